@@ -2,6 +2,8 @@
 //!
 //! See [AccountEndpoints](trait.AccountEndpoints.html) trait for the REST API.
 
+use crate::changes::ChangeInfo;
+use crate::Result;
 use serde_derive::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
@@ -10,7 +12,148 @@ use serde_with::skip_serializing_none;
 // ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
 
 /// This trait describes the account related REST endpoints.
-pub trait AccountEndpoints {}
+///
+/// An `account_id` identifies an account and can be given as the numeric account ID, the username,
+/// the email address, `Full Name <email@example.com>`, or the string `self` for the calling user.
+pub trait AccountEndpoints {
+  /// Returns an account as an `AccountInfo` entity.
+  fn get_account(&mut self, account_id: &str) -> Result<AccountInfo>;
+
+  /// Queries accounts visible to the caller.
+  ///
+  /// The query string must be provided by the q parameter. The n parameter can be used to limit the returned results.
+  ///
+  /// As result a list of `AccountInfo` entities is returned.
+  /// If the number of accounts matching the query exceeds either the internal limit or a supplied n query
+  /// parameter, the last account object has a `_more_accounts: true` JSON field set.
+  fn query_accounts(&mut self, params: &AccountQueryParams) -> Result<Vec<AccountInfo>>;
+
+  /// Retrieves the full name of an account.
+  ///
+  /// If the account does not have a name an empty string is returned.
+  fn get_account_name(&mut self, account_id: &str) -> Result<String>;
+
+  /// Sets the full name of an account.
+  ///
+  /// The new account name must be provided in the request body inside an `AccountNameInput` entity.
+  ///
+  /// As response the new account name is returned.
+  /// If the name was deleted, `None` is returned.
+  fn set_account_name(&mut self, account_id: &str, input: &AccountNameInput) -> Result<Option<String>>;
+
+  /// Deletes the name of an account.
+  fn delete_account_name(&mut self, account_id: &str) -> Result<()>;
+
+  /// Retrieves the status of an account.
+  ///
+  /// If the account does not have a status an empty string is returned.
+  fn get_account_status(&mut self, account_id: &str) -> Result<String>;
+
+  /// Sets the status of an account.
+  ///
+  /// The new account status must be provided in the request body inside a `StatusInput` entity.
+  ///
+  /// As response the new account status is returned.
+  /// If the status was deleted, `None` is returned.
+  fn set_account_status(&mut self, account_id: &str, input: &StatusInput) -> Result<Option<String>>;
+
+  /// Checks if an account is active.
+  ///
+  /// Gerrit responds with `200 OK` and the body `ok` if the account is active,
+  /// and with `204 No Content` if the account is inactive.
+  fn get_active(&mut self, account_id: &str) -> Result<bool>;
+
+  /// Sets the account state to active.
+  ///
+  /// Succeeds as well if the account was already active.
+  fn set_active(&mut self, account_id: &str) -> Result<()>;
+
+  /// Sets the account state to inactive.
+  ///
+  /// If the account was already inactive the response is `409 Conflict`.
+  fn delete_active(&mut self, account_id: &str) -> Result<()>;
+
+  /// Returns the SSH keys of an account.
+  ///
+  /// As response a list of `SshKeyInfo` entities is returned.
+  fn list_ssh_keys(&mut self, account_id: &str) -> Result<Vec<SshKeyInfo>>;
+
+  /// Retrieves an SSH key of a user.
+  ///
+  /// As response an `SshKeyInfo` entity is returned.
+  fn get_ssh_key(&mut self, account_id: &str, seq: u32) -> Result<SshKeyInfo>;
+
+  /// Adds an SSH key for a user.
+  ///
+  /// The SSH public key is sent in the request body as plain text.
+  ///
+  /// As response an `SshKeyInfo` entity is returned that describes the new SSH key.
+  fn add_ssh_key(&mut self, account_id: &str, key: &str) -> Result<SshKeyInfo>;
+
+  /// Deletes an SSH key of a user.
+  fn delete_ssh_key(&mut self, account_id: &str, seq: u32) -> Result<()>;
+
+  /// Gets the changes that were starred with the default star by the identified user account.
+  ///
+  /// This URL endpoint is functionally identical to the changes query `GET /changes/?q=is:starred`.
+  ///
+  /// As result a list of `ChangeInfo` entities is returned.
+  fn get_starred_changes(&mut self, account_id: &str) -> Result<Vec<ChangeInfo>>;
+
+  /// Star a change with the default label.
+  ///
+  /// Starred changes are returned for the search query `is:starred` or `starredby:USER`
+  /// and automatically notify the user whenever updates are made to the change.
+  fn star_change(&mut self, account_id: &str, change_id: &str) -> Result<()>;
+
+  /// Unstar a change. Removes the default star label from the change.
+  fn unstar_change(&mut self, account_id: &str, change_id: &str) -> Result<()>;
+
+  /// Get star labels from a change.
+  ///
+  /// As response the star labels that the user applied on the change are returned, sorted alphabetically.
+  fn get_star_labels(&mut self, account_id: &str, change_id: &str) -> Result<Vec<String>>;
+
+  /// Update star labels on a change.
+  ///
+  /// The star labels to be added or removed must be specified in the request body as a `StarsInput` entity.
+  ///
+  /// As response the star labels that the user applied on the change are returned, sorted alphabetically.
+  fn update_star_labels(&mut self, account_id: &str, change_id: &str, input: &StarsInput) -> Result<Vec<String>>;
+
+  /// Returns the global capabilities that are enabled for the specified user.
+  ///
+  /// The result can be limited to the capabilities named in `filter`; all are returned if it is empty.
+  ///
+  /// As response the global capabilities are returned as a `CapabilityInfo` entity.
+  fn get_account_capabilities(&mut self, account_id: &str, filter: &[&str]) -> Result<CapabilityInfo>;
+
+  /// Retrieves the avatar image of an account.
+  ///
+  /// The `size` is the desired size of the image in pixels, which the avatar provider may not support exactly.
+  ///
+  /// As response the raw image data is returned, following the redirect to the avatar provider.
+  /// Fails with `Error::NotFound` if no avatar is available for the account.
+  fn get_avatar(&mut self, account_id: &str, size: Option<u32>) -> Result<Vec<u8>>;
+
+  /// Retrieves all projects a user is watching.
+  ///
+  /// As response the watched projects are returned as a list of `ProjectWatchInfo` entities.
+  /// The result is sorted by project name in ascending order.
+  fn get_watched_projects(&mut self, account_id: &str) -> Result<Vec<ProjectWatchInfo>>;
+
+  /// Add new projects to watch or update existing watched projects.
+  ///
+  /// Projects can be identified by project name. The same project can be watched with different filters.
+  ///
+  /// As response the updated list of watched projects is returned, sorted by project name.
+  fn add_watched_projects(&mut self, account_id: &str, watches: &[ProjectWatchInfo]) -> Result<Vec<ProjectWatchInfo>>;
+
+  /// Projects posted to this endpoint will no longer be watched.
+  ///
+  /// Only the project and filter of the given watches are used to identify the watches to remove.
+  fn delete_watched_projects(&mut self, account_id: &str, watches: &[ProjectWatchInfo]) -> Result<()>;
+}
 
 // ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
 // JSON Entities
@@ -73,6 +216,34 @@ pub struct AccountInput {
   pub groups: Option<Vec<u32>>,
 }
 
+/// The AccountNameInput entity contains information for setting a name for an account.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountNameInput {
+  /// The new full name of the account.
+  /// If not set or if set to an empty string, the account name is deleted.
+  pub name: Option<String>,
+}
+
+/// The StarsInput entity contains star labels that should be added to or removed from a change.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StarsInput {
+  /// List of labels to add to the change.
+  pub add: Option<Vec<String>>,
+  /// List of labels to remove from the change.
+  pub remove: Option<Vec<String>>,
+}
+
+/// The StatusInput entity contains information for setting a status for an account.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusInput {
+  /// The new status of the account.
+  /// If not set or if set to an empty string, the account status is deleted.
+  pub status: Option<String>,
+}
+
 /// The AccountInfo entity contains information about an avatar image of an account.
 #[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -85,6 +256,83 @@ pub struct AvatarInfo {
   pub width: Option<u32>,
 }
 
+/// The CapabilityInfo entity contains information about the global capabilities of a user.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityInfo {
+  /// Whether the user has the Access Database capability.
+  #[serde(default)]
+  pub access_database: bool,
+  /// Whether the user has the Administrate Server capability.
+  #[serde(default)]
+  pub administrate_server: bool,
+  /// Whether the user has the Create Account capability.
+  #[serde(default)]
+  pub create_account: bool,
+  /// Whether the user has the Create Group capability.
+  #[serde(default)]
+  pub create_group: bool,
+  /// Whether the user has the Create Project capability.
+  #[serde(default)]
+  pub create_project: bool,
+  /// Whether the user has the Email Reviewers capability.
+  #[serde(default)]
+  pub email_reviewers: bool,
+  /// Whether the user has the Flush Caches capability.
+  #[serde(default)]
+  pub flush_caches: bool,
+  /// Whether the user has the Kill Task capability.
+  #[serde(default)]
+  pub kill_task: bool,
+  /// Whether the user has the Maintain Server capability.
+  #[serde(default)]
+  pub maintain_server: bool,
+  /// Whether the user has the Modify Account capability.
+  #[serde(default)]
+  pub modify_account: bool,
+  /// The name of the thread pool used by the user, see Priority capability.
+  pub priority: Option<String>,
+  /// The Query Limit of the user as QueryLimitInfo.
+  pub query_limit: Option<QueryLimitInfo>,
+  /// Whether the user has the Run As capability.
+  #[serde(default)]
+  pub run_as: bool,
+  /// Whether the user has the Run Garbage Collection capability.
+  #[serde(rename = "runGC", default)]
+  pub run_gc: bool,
+  /// Whether the user has the Stream Events capability.
+  #[serde(default)]
+  pub stream_events: bool,
+  /// Whether the user has the View Access capability.
+  #[serde(default)]
+  pub view_access: bool,
+  /// Whether the user has the View All Accounts capability.
+  #[serde(default)]
+  pub view_all_accounts: bool,
+  /// Whether the user has the View Caches capability.
+  #[serde(default)]
+  pub view_caches: bool,
+  /// Whether the user has the View Connections capability.
+  #[serde(default)]
+  pub view_connections: bool,
+  /// Whether the user has the View Plugins capability.
+  #[serde(default)]
+  pub view_plugins: bool,
+  /// Whether the user has the View Queue capability.
+  #[serde(default)]
+  pub view_queue: bool,
+}
+
+/// The QueryLimitInfo entity contains information about the Query Limit of a user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryLimitInfo {
+  /// Lower limit.
+  pub min: u32,
+  /// Upper limit.
+  pub max: u32,
+}
+
 /// The GpgKeyInfo entity contains information about a GPG public key.
 #[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -110,6 +358,44 @@ pub struct GpgKeyInfo {
   pub problems: Option<Vec<String>>,
 }
 
+/// The ProjectWatchInfo entity contains information about a project watch for a user.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectWatchInfo {
+  /// The name of the project.
+  pub project: String,
+  /// A filter string to be applied to the project.
+  pub filter: Option<String>,
+  /// Notify on new changes.
+  pub notify_new_changes: Option<bool>,
+  /// Notify on new patch sets.
+  pub notify_new_patch_sets: Option<bool>,
+  /// Notify on all comments.
+  pub notify_all_comments: Option<bool>,
+  /// Notify on submitted changes.
+  pub notify_submitted_changes: Option<bool>,
+  /// Notify on abandoned changes.
+  pub notify_abandoned_changes: Option<bool>,
+}
+
+/// The SshKeyInfo entity contains information about an SSH key of a user.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SshKeyInfo {
+  /// The sequence number of the SSH key.
+  pub seq: u32,
+  /// The complete public SSH key.
+  pub ssh_public_key: String,
+  /// The encoded key.
+  pub encoded_key: String,
+  /// The algorithm of the SSH key.
+  pub algorithm: String,
+  /// The comment of the SSH key.
+  pub comment: Option<String>,
+  /// Whether the SSH key is valid.
+  pub valid: bool,
+}
+
 /// Key check status.
 #[derive(Debug, Display, PartialEq, Eq, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -122,3 +408,37 @@ pub enum KeyStatus {
   /// A TRUSTED key is valid, and the system knows enough about the key and its origin to trust it.
   Trusted,
 }
+
+// ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+// OPTIONS
+// ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Query parameters available for the query accounts endpoint.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AccountQueryParams {
+  /// Query string for searching accounts, e.g. `is:active`.
+  #[serde(rename = "q")]
+  pub search_query: Option<String>,
+  /// Additional Options to extend the query results.
+  #[serde(rename = "o")]
+  pub additional_opts: Option<Vec<AccountOpt>>,
+  /// Limit the returned results to no more than X records.
+  #[serde(rename = "n")]
+  pub limit: Option<u32>,
+  /// The start query parameter can be supplied to skip a number of accounts from the list.
+  #[serde(rename = "S")]
+  pub start: Option<u32>,
+}
+
+/// Additional fields can be obtained by adding `o` parameters to the query accounts endpoint.
+#[derive(AsRefStr, Display, PartialEq, Eq, Clone, Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum AccountOpt {
+  /// Includes full name, preferred email, username, avatars and status for each account.
+  Details,
+  /// Includes all registered emails. Secondary emails are only included if the calling user has
+  /// the Modify Account capability.
+  AllEmails,
+}
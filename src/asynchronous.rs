@@ -0,0 +1,282 @@
+//! Asynchronous Gerrit REST API client.
+//!
+//! Available with the `async` cargo feature. See [AsyncGerritRestApi](struct.AsyncGerritRestApi.html).
+
+use crate::changes::{change_url, review_url, *};
+use crate::error::RequestInfo;
+use crate::handler::Response;
+use crate::Result;
+use ::http::StatusCode;
+use serde::Serialize;
+use url::Url;
+
+/// Asynchronous Gerrit REST API over HTTP, backed by `reqwest`.
+///
+/// Methods take `&self`, so a single client can drive many concurrent requests from an async runtime.
+/// Responses are checked and parsed the same way as in the synchronous
+/// [GerritRestApi](../struct.GerritRestApi.html).
+///
+/// Only HTTP Basic authentication is supported.
+#[derive(Debug, Clone)]
+pub struct AsyncGerritRestApi {
+  client: reqwest::Client,
+  base_url: Url,
+  username: String,
+  password: String,
+}
+
+impl AsyncGerritRestApi {
+  /// Create a new AsyncGerritRestApi with the host url, username and HTTP password.
+  pub fn new(base_url: Url, username: &str, password: &str) -> Result<Self> {
+    let client = reqwest::Client::builder().build()?;
+    Ok(Self {
+      client,
+      base_url,
+      username: username.into(),
+      password: password.into(),
+    })
+  }
+
+  /// Enable/Disable SSL verification of both host and peer.
+  ///
+  /// Disabling it accepts invalid certificates, including ones that do not match the host name.
+  pub fn ssl_verify(mut self, enable: bool) -> Result<Self> {
+    self.client = reqwest::Client::builder()
+      .danger_accept_invalid_certs(!enable)
+      .build()?;
+    Ok(self)
+  }
+
+  /// Queries changes visible to the caller.
+  ///
+  /// See [ChangeEndpoints::query_changes](../changes/trait.ChangeEndpoints.html#tymethod.query_changes).
+  pub async fn query_changes(&self, query: &QueryParams) -> Result<Vec<Vec<ChangeInfo>>> {
+    if query.search_queries.as_ref().map_or(true, Vec::is_empty) {
+      return Ok(Vec::new());
+    }
+    let json = self.get(&query.url()?).await?.expect(StatusCode::OK)?.json()?;
+    query.parse_changes(&json)
+  }
+
+  /// Retrieves a change.
+  ///
+  /// See [ChangeEndpoints::get_change](../changes/trait.ChangeEndpoints.html#tymethod.get_change).
+  pub async fn get_change(&self, change_id: &str, additional_opts: Option<Vec<AdditionalOpt>>) -> Result<ChangeInfo> {
+    self
+      .get(&change_url(change_id, additional_opts)?)
+      .await?
+      .expect_json(StatusCode::OK)
+  }
+
+  /// Sets a review on a revision.
+  ///
+  /// The input is validated before sending the request, the same as in the synchronous client.
+  /// See [ChangeEndpoints::set_review](../changes/trait.ChangeEndpoints.html#tymethod.set_review).
+  pub async fn set_review(&self, change_id: &str, revision_id: &str, input: &ReviewInput) -> Result<ReviewResult> {
+    input.validate()?;
+    self
+      .post_json(&review_url(change_id, revision_id), input)
+      .await?
+      .expect_json(StatusCode::OK)
+  }
+
+  async fn get(&self, url: &str) -> Result<Response> {
+    let request = self
+      .client
+      .get(self.url(url)?)
+      .header(::http::header::ACCEPT, "application/json");
+    self.send(request).await
+  }
+
+  async fn post_json<T>(&self, url: &str, data: &T) -> Result<Response>
+  where
+    T: Serialize + ?Sized,
+  {
+    let data = serde_json::to_vec(data)?;
+    let request = self
+      .client
+      .post(self.url(url)?)
+      .header(::http::header::CONTENT_TYPE, "application/json")
+      .body(data);
+    self.send(request).await
+  }
+
+  fn url(&self, path_and_query: &str) -> Result<Url> {
+    let url = self.base_url.join(path_and_query).map_err(crate::http::Error::from)?;
+    Ok(url)
+  }
+
+  async fn send(&self, request: reqwest::RequestBuilder) -> Result<Response> {
+    let request = request.basic_auth(&self.username, Some(&self.password)).build()?;
+    let request_info = RequestInfo {
+      method: request.method().to_string(),
+      url: request.url().to_string(),
+    };
+    let response = self.client.execute(request).await?;
+    let code = response.status();
+    let headers = response
+      .headers()
+      .iter()
+      .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned()))
+      .collect();
+    let body = response.bytes().await?;
+    Ok(Response {
+      code,
+      headers,
+      message: body.to_vec().into(),
+      request: request_info,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::http::mock::CHANGE;
+  use std::io::{BufRead, BufReader, Read, Write};
+  use std::net::TcpListener;
+  use std::thread::JoinHandle;
+
+  /// Request received by the mock server, as the request line, the headers in lowercase and the body.
+  struct Received {
+    request_line: String,
+    headers: Vec<String>,
+    body: String,
+  }
+
+  /// Start a server on a local port that answers a single request with the given status line and body.
+  fn serve(status: &'static str, body: String) -> (AsyncGerritRestApi, JoinHandle<Received>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+    let server = std::thread::spawn(move || {
+      let (stream, _) = listener.accept().unwrap();
+      let mut reader = BufReader::new(stream);
+      let mut request_line = String::new();
+      reader.read_line(&mut request_line).unwrap();
+      let mut headers = Vec::new();
+      loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        if line.trim().is_empty() {
+          break;
+        }
+        headers.push(line.trim().to_lowercase());
+      }
+      let length = headers
+        .iter()
+        .find_map(|h| h.strip_prefix("content-length:"))
+        .map_or(0, |len| len.trim().parse().unwrap());
+      let mut request_body = vec![0; length];
+      reader.read_exact(&mut request_body).unwrap();
+      let response = format!(
+        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+      );
+      reader.get_mut().write_all(response.as_bytes()).unwrap();
+      Received {
+        request_line: request_line.trim().into(),
+        headers,
+        body: String::from_utf8(request_body).unwrap(),
+      }
+    });
+    (AsyncGerritRestApi::new(url, "jdoe", "secret").unwrap(), server)
+  }
+
+  fn json(body: &str) -> String {
+    format!(")]}}'\n{}", body)
+  }
+
+  #[tokio::test]
+  async fn query_changes() {
+    let (gerrit, server) = serve("200 OK", json(&format!("[{}]", CHANGE)));
+    let query = QueryParams {
+      search_queries: Some(vec![QueryStr::Raw("status:open".into())]),
+      ..Default::default()
+    };
+    let changes = gerrit.query_changes(&query).await.unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0][0].number, 3965);
+    let received = server.join().unwrap();
+    assert_eq!(received.request_line, "GET /a/changes/?q=status%3Aopen HTTP/1.1");
+    assert!(received.headers.contains(&"accept: application/json".into()));
+    assert!(received.headers.iter().any(|h| h.starts_with("authorization: basic ")));
+  }
+
+  #[tokio::test]
+  async fn get_change() {
+    let (gerrit, server) = serve("200 OK", json(CHANGE));
+    let change = gerrit
+      .get_change("3965", Some(vec![AdditionalOpt::CurrentRevision]))
+      .await
+      .unwrap();
+    assert_eq!(change.subject, "Implementing Feature X");
+    let received = server.join().unwrap();
+    assert_eq!(
+      received.request_line,
+      "GET /a/changes/3965/?o=CURRENT_REVISION HTTP/1.1"
+    );
+  }
+
+  #[tokio::test]
+  async fn get_change_not_found() {
+    let (gerrit, server) = serve("404 Not Found", "Not found: 1\n".into());
+    let error = gerrit.get_change("1", None).await.unwrap_err();
+    assert!(error.is_not_found());
+    assert_eq!(error.to_string(), "Not found: 1");
+    server.join().unwrap();
+  }
+
+  #[tokio::test]
+  async fn get_change_without_magic_prefix() {
+    let (gerrit, server) = serve("200 OK", "<html>Sign in</html>".into());
+    let error = gerrit.get_change("3965", None).await.unwrap_err();
+    assert_eq!(error.to_string(), "Unexpected non-JSON response: <html>Sign in</html>");
+    server.join().unwrap();
+  }
+
+  #[tokio::test]
+  async fn set_review() {
+    let (gerrit, server) = serve("200 OK", json(r#"{ "labels": { "Code-Review": -1 } }"#));
+    let input: ReviewInput =
+      serde_json::from_str(r#"{ "message": "Some nits", "labels": { "Code-Review": -1 } }"#).unwrap();
+    let result = gerrit.set_review("3965", "current", &input).await.unwrap();
+    assert_eq!(result.labels.unwrap()["Code-Review"], -1);
+    let received = server.join().unwrap();
+    assert_eq!(
+      received.request_line,
+      "POST /a/changes/3965/revisions/current/review HTTP/1.1"
+    );
+    assert!(received.headers.contains(&"content-type: application/json".into()));
+    let body: serde_json::Value = serde_json::from_str(&received.body).unwrap();
+    assert_eq!(body["message"], "Some nits");
+    assert_eq!(body["labels"]["Code-Review"], -1);
+  }
+
+  #[tokio::test]
+  async fn set_review_is_validated_before_sending() {
+    let gerrit = AsyncGerritRestApi::new(Url::parse("http://127.0.0.1:9/").unwrap(), "jdoe", "secret").unwrap();
+    let input: ReviewInput = serde_json::from_str(r#"{ "ready": true, "work_in_progress": true }"#).unwrap();
+    let error = gerrit.set_review("3965", "current", &input).await.unwrap_err();
+    assert!(matches!(error, crate::error::Error::WrongQuery(_)));
+    let input: ReviewInput = serde_json::from_str(
+      r#"{ "robot_comments": { "lib.rs": [{
+        "robot_id": "clippy",
+        "robot_run_id": "run-1",
+        "fix_suggestions": [{
+          "description": "Remove dead code",
+          "replacements": [
+            { "path": "lib.rs", "range": { "start_line": 1, "start_character": 0, "end_line": 4, "end_character": 0 },
+              "replacement": "" },
+            { "path": "lib.rs", "range": { "start_line": 2, "start_character": 0, "end_line": 5, "end_character": 0 },
+              "replacement": "" }
+          ]
+        }]
+      }] } }"#,
+    )
+    .unwrap();
+    let error = gerrit.set_review("3965", "current", &input).await.unwrap_err();
+    assert!(matches!(error, crate::error::Error::WrongQuery(_)));
+  }
+}
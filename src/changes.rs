@@ -4,12 +4,14 @@
 
 use crate::accounts::{AccountInfo, AccountInput, GpgKeyInfo};
 use crate::details::Timestamp;
+pub use crate::groups::GroupBaseInfo;
 use crate::Result;
 use serde::Serializer;
 use serde_derive::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Display, Error, Formatter};
+use std::path::Path;
 
 // ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
 // REST API
@@ -40,8 +42,29 @@ pub trait ChangeEndpoints {
   /// The S or start query parameter can be supplied to skip a number of changes from the list.
   /// Clients are allowed to specify more than one query by setting the q parameter multiple times.
   /// In this case the result is an array of arrays, one per query in the same order the queries were given in.
+  ///
+  /// The returned outer list always has one inner list per query in `search_queries`, in the same order.
+  /// Without any query no request is made and the list is empty, see `query_changes_single` for that case.
   fn query_changes(&mut self, query: &QueryParams) -> Result<Vec<Vec<ChangeInfo>>>;
 
+  /// Queries changes visible to the caller with a single query.
+  ///
+  /// Same as `query_changes`, but returns the changes of the only query directly.
+  /// Without any query the server default applies.
+  /// If more than one query is given in `search_queries`, `Error::InvalidInput` is returned.
+  fn query_changes_single(&mut self, query: &QueryParams) -> Result<Vec<ChangeInfo>>;
+
+  /// Queries changes visible to the caller with a single query, fetching further pages as needed.
+  ///
+  /// The returned iterator yields the changes of the query one by one. Whenever the last change of a page
+  /// has `more_changes` set, the next page is requested by advancing the start parameter by the number
+  /// of changes received. The `limit` of the query, if any, is used as the page size.
+  ///
+  /// Iteration stops after the first error, which is yielded as the last item.
+  fn query_changes_paged(&mut self, query: &QueryParams) -> ChangeQueryIter<'_, Self>
+  where
+    Self: Sized;
+
   /// Retrieves a change.
   ///
   /// Additional fields can be obtained by adding o parameters, each option requires more database
@@ -51,6 +74,14 @@ pub trait ChangeEndpoints {
   /// As response a `ChangeInfo` entity is returned that describes the change.
   fn get_change(&mut self, change_id: &str, additional_opts: Option<Vec<AdditionalOpt>>) -> Result<ChangeInfo>;
 
+  /// Retrieves a change as raw JSON.
+  ///
+  /// Performs the same request as `get_change`, but returns the JSON value as is, including any fields
+  /// not modeled by `ChangeInfo`, e.g. ones provided by plugins.
+  fn get_change_raw(
+    &mut self, change_id: &str, additional_opts: Option<Vec<AdditionalOpt>>,
+  ) -> Result<serde_json::Value>;
+
   /// Retrieves a change with labels, detailed labels, detailed accounts, reviewer updates, and messages.
   ///
   /// Additional fields can be obtained by adding o parameters, each option requires more database
@@ -63,6 +94,42 @@ pub trait ChangeEndpoints {
   /// REJECTED > APPROVED > DISLIKED > RECOMMENDED.
   fn get_change_detail(&mut self, change_id: &str, additional_opts: Option<Vec<AdditionalOpt>>) -> Result<ChangeInfo>;
 
+  /// Retrieves a change with its reviewer updates, along with the history of each reviewer.
+  ///
+  /// The `REVIEWER_UPDATES` option is requested. Reviewer updates are only available when NoteDb is enabled,
+  /// otherwise the returned history is empty.
+  ///
+  /// As response a `ChangeInfo` entity is returned together with the `ReviewerHistory` of its reviewers.
+  fn get_change_with_reviewer_history(&mut self, change_id: &str) -> Result<(ChangeInfo, Vec<ReviewerHistory>)>;
+
+  /// Retrieves a change along with its ETag.
+  ///
+  /// The ETag can be passed to `get_change_if_changed` later to avoid refetching an unchanged change.
+  fn get_change_with_etag(&mut self, change_id: &str) -> Result<(ChangeInfo, Option<String>)>;
+
+  /// Retrieves a change only if it changed since the given ETag was obtained.
+  ///
+  /// The ETag is sent in the `If-None-Match` header. If the change is unchanged the server
+  /// responds with `304 Not Modified` and `None` is returned.
+  fn get_change_if_changed(&mut self, change_id: &str, etag: &str) -> Result<Option<ChangeInfo>>;
+
+  /// Retrieves a change with its references to external tracking systems.
+  ///
+  /// The `TRACKING_IDS` option is requested, so `ChangeInfo::tracking_ids_for_system` can be used on the result.
+  fn get_change_with_tracking_ids(&mut self, change_id: &str) -> Result<ChangeInfo>;
+
+  /// Retrieves the difference between two historical states of a change.
+  ///
+  /// The `old_meta` parameter can be provided to specify the SHA-1 of the older NoteDb meta state of the change.
+  /// If not provided, the parent of the newer state is used. The `new_meta` parameter specifies the newer
+  /// NoteDb meta state, and defaults to the current state of the change.
+  ///
+  /// As response a `ChangeInfoDifference` entity is returned that describes the fields added and removed
+  /// between both states.
+  fn get_change_meta_diff(
+    &mut self, change_id: &str, old_meta: Option<&str>, new_meta: Option<&str>,
+  ) -> Result<ChangeInfoDifference>;
+
   /// Update an existing change by using a `MergePatchSetInput` entity.
   ///
   /// Gerrit will create a merge commit based on the information of `MergePatchSetInput` and add
@@ -158,6 +225,21 @@ pub trait ChangeEndpoints {
   /// the response is “409 Conflict” and the error message is contained in the response body.
   fn restore_change(&mut self, change_id: &str, restore: &RestoreInput) -> Result<ChangeInfo>;
 
+  /// Abandons all changes matching the query.
+  ///
+  /// All pages of matching changes are queried first, see `query_changes_paged`, and then the changes are
+  /// abandoned one by one, so abandoning them does not shift the pages still to be fetched.
+  /// A failure to abandon a change does not stop the others from being abandoned.
+  ///
+  /// As response the legacy numeric ID of each matching change is returned along with the outcome of
+  /// abandoning it. Only a failure of the query itself is returned as an error.
+  fn abandon_matching(&mut self, query: &QueryStr, abandon: &AbandonInput) -> Result<Vec<(u32, Result<ChangeInfo>)>>;
+
+  /// Restores all changes matching the query.
+  ///
+  /// Same as `abandon_matching`, but restoring each matching change.
+  fn restore_matching(&mut self, query: &QueryStr, restore: &RestoreInput) -> Result<Vec<(u32, Result<ChangeInfo>)>>;
+
   /// Rebases a change.
   ///
   /// Optionally, the parent revision can be changed to another patch set through the `RebaseInput` entity.
@@ -238,6 +320,19 @@ pub trait ChangeEndpoints {
   /// the response is “409 Conflict” and the error message is contained in the response body.
   fn submit_change(&mut self, change_id: &str, submit: &SubmitInput) -> Result<ChangeInfo>;
 
+  /// Submits a change and returns its submission id.
+  ///
+  /// The submission id is shared by all changes that were submitted together, and can be used
+  /// to query them with the `submissionid:` search operator.
+  ///
+  /// Fails with `Error::MissingResponseField` if the submitted change has no `submission_id`.
+  fn submit_change_and_get_submission(&mut self, change_id: &str, submit: &SubmitInput) -> Result<String>;
+
+  /// Queries all changes that were submitted together in the given submission.
+  ///
+  /// This is a shortcut for querying changes with the `submissionid:{submission_id}` search operator.
+  fn query_by_submission(&mut self, submission_id: &str) -> Result<Vec<ChangeInfo>>;
+
   /// Computes list of all changes which are submitted when Submit is called for this change,
   /// including the current change itself.
   ///
@@ -271,20 +366,34 @@ pub trait ChangeEndpoints {
   /// Returns a map of file paths to lists of `CommentInfo` entries. The entries in the map are
   /// sorted by file path, and the comments for each path are sorted by patch set number.
   /// Each comment has the patch_set and author fields set.
-  fn list_change_comments(&mut self, change_id: &str) -> Result<BTreeMap<String, CommentInfo>>;
+  fn list_change_comments(&mut self, change_id: &str) -> Result<BTreeMap<String, Vec<CommentInfo>>>;
+
+  /// Lists the published comments of all revisions of the change as a single flat list.
+  ///
+  /// Each entry pairs the file path with the `CommentInfo`, which has the patch_set and author fields set.
+  /// The entries are sorted by patch set number and then by the time the comment was written.
+  fn get_all_comments(&mut self, change_id: &str) -> Result<Vec<(String, CommentInfo)>>;
 
   /// Lists the robot comments of all revisions of the change.
   ///
   /// Return a map that maps the file path to a list of RobotCommentInfo entries.
   /// The entries in the map are sorted by file path.
-  fn list_change_robot_comments(&mut self, change_id: &str) -> Result<BTreeMap<String, RobotCommentInfo>>;
+  fn list_change_robot_comments(&mut self, change_id: &str) -> Result<BTreeMap<String, Vec<RobotCommentInfo>>>;
 
   /// Lists the draft comments of all revisions of the change that belong to the calling user.
   ///
   /// Returns a map of file paths to lists of `CommentInfo` entries.
   /// The entries in the map are sorted by file path, and the comments for each path are sorted by
   /// patch set number. Each comment has the `patch_set` field set, and no `author`.
-  fn list_change_drafts(&mut self, change_id: &str) -> Result<BTreeMap<String, CommentInfo>>;
+  fn list_change_drafts(&mut self, change_id: &str) -> Result<BTreeMap<String, Vec<CommentInfo>>>;
+
+  /// Deletes all draft comments of the calling user on all revisions of the change.
+  ///
+  /// The drafts are listed with `list_change_drafts` and then deleted one by one with `delete_draft`,
+  /// so a failure may leave some of the drafts in place.
+  ///
+  /// As response the number of deleted draft comments is returned.
+  fn delete_all_drafts(&mut self, change_id: &str) -> Result<usize>;
 
   /// Performs consistency checks on the change, and returns a ChangeInfo entity with the problems field
   /// set to a list of ProblemInfo entities.
@@ -299,7 +408,7 @@ pub trait ChangeEndpoints {
   /// Some fixes have options controlling their behavior, which can be set in the `FixInput` entity body.
   ///
   /// Only the change owner, a project owner, or an administrator may fix changes.
-  fn fix_change(&mut self, change_id: &str) -> Result<ChangeInfo>;
+  fn fix_change(&mut self, change_id: &str, input: Option<&FixInput>) -> Result<ChangeInfo>;
 
   /// Marks the change as not ready for review yet.
   ///
@@ -340,6 +449,9 @@ pub trait ChangeEndpoints {
   /// The change will not be shown in the incoming reviews dashboard, and email notifications will be suppressed.
   ///
   /// Ignoring a change does not cause the change’s "updated" timestamp to be modified, and the owner is not notified.
+  ///
+  /// Ignoring does not remove the caller from the attention set of the change.
+  /// Ignored changes can be queried with the `is:ignored` operator.
   fn ignore_change(&mut self, change_id: &str) -> Result<()>;
 
   /// Un-marks a change as ignored.
@@ -351,6 +463,9 @@ pub trait ChangeEndpoints {
   ///
   /// This differs from the ignore endpoint, which will mute emails and hide the change from dashboard
   /// completely until it is unignored again.
+  ///
+  /// Older Gerrit versions called this "mute"; there is no separate mute endpoint on current servers.
+  /// Like ignoring, it does not modify the attention set of the change.
   fn mark_as_reviewed(&mut self, change_id: &str) -> Result<()>;
 
   /// Marks a change as unreviewed.
@@ -370,6 +485,7 @@ pub trait ChangeEndpoints {
   /// NOTE: Hashtags are only available when NoteDb is enabled.
   ///
   /// The hashtags to add or remove must be provided in the request body inside a `HashtagsInput` entity.
+  /// They are trimmed and checked before sending, see `HashtagsInput::normalized`.
   ///
   /// As response the change's hashtags are returned as a list of strings.
   fn set_hashtags(&mut self, change_id: &str, input: &HashtagsInput) -> Result<Vec<String>>;
@@ -396,6 +512,57 @@ pub trait ChangeEndpoints {
     &mut self, change_id: &str, message_id: &str, input: Option<&DeleteChangeMessageInput>,
   ) -> Result<ChangeMessageInfo>;
 
+  /// Retrieves the commit message from the change edit.
+  ///
+  /// If a change edit doesn’t exist for this change, the change’s commit message is returned.
+  ///
+  /// As response the commit message is returned, decoded from the base64 encoded string sent by the server.
+  /// Fails with `Error::InvalidUtf8Response` if the decoded message is not valid UTF-8.
+  fn get_edit_message(&mut self, change_id: &str) -> Result<String>;
+
+  /// Modifies the commit message of the change edit.
+  ///
+  /// The commit message to be set must be provided in the request body inside a `ChangeEditMessageInput` entity.
+  ///
+  /// If a change edit doesn’t exist for this change yet, it is created.
+  fn set_edit_message(&mut self, change_id: &str, input: &ChangeEditMessageInput) -> Result<()>;
+
+  /// Retrieves a change edit details.
+  ///
+  /// If `list_files` is set, the files of the change edit are included in the response.
+  ///
+  /// As response an `EditInfo` entity is returned that describes the change edit,
+  /// or `None` if the change edit doesn’t exist for this change.
+  fn get_change_edit(&mut self, change_id: &str, list_files: bool) -> Result<Option<EditInfo>>;
+
+  /// Puts the content of a file into the change edit.
+  ///
+  /// The file is given by its path, which is URL encoded by this method, and the content is sent as is.
+  /// When a change edit doesn’t exist for this change yet, it is created.
+  fn put_edit_file(&mut self, change_id: &str, file_path: &str, content: &[u8]) -> Result<()>;
+
+  /// Deletes a file from the change edit.
+  ///
+  /// The file is given by its path, which is URL encoded by this method.
+  /// This deletes the file from the repository completely. This is not the same as reverting or restoring a file.
+  fn delete_edit_file(&mut self, change_id: &str, file_path: &str) -> Result<()>;
+
+  /// Promotes change edit to a regular patch set.
+  ///
+  /// Options can be provided in the request body as a `PublishChangeEditInput` entity.
+  fn publish_change_edit(&mut self, change_id: &str, input: Option<&PublishChangeEditInput>) -> Result<()>;
+
+  /// Rebases change edit on top of the latest patch set.
+  ///
+  /// When change was rebased on top of the latest patch set, response “204 No Content” is returned.
+  /// When change edit is already based on top of the latest patch set, the response “409 Conflict” is returned.
+  fn rebase_change_edit(&mut self, change_id: &str) -> Result<()>;
+
+  /// Deletes change edit.
+  ///
+  /// As response “204 No Content” is returned.
+  fn delete_change_edit(&mut self, change_id: &str) -> Result<()>;
+
   /// Lists the reviewers of a change.
   ///
   /// As result a list of `ReviewerInfo` entries is returned.
@@ -412,12 +579,12 @@ pub trait ChangeEndpoints {
   ///  * are not already reviewer on the change
   ///  * don’t own the change
   ///
-  /// Groups can be excluded from the results by specifying the 'exclude-groups' request parameter.
+  /// Groups can be excluded from the results by setting `exclude_groups` in the `SuggestReviewersParams`.
   ///
-  /// To suggest CCs reviewer-state=CC can be specified as additional URL parameter.
+  /// To suggest CCs the `reviewer_state` can be set to `ReviewerState::Cc`.
   /// This includes existing reviewers in the result, but excludes existing CCs.
   fn suggest_reviewers(
-    &mut self, change_id: &str, query_str: &str, limit: Option<u32>, exclude_groups: bool, cc: bool,
+    &mut self, change_id: &str, params: &SuggestReviewersParams,
   ) -> Result<Vec<SuggestedReviewerInfo>>;
 
   /// Retrieves a reviewer of a change.
@@ -489,6 +656,25 @@ pub trait ChangeEndpoints {
   /// Web links are only included if the links option was set.
   fn get_merge_list(&mut self, change_id: &str, revision_id: &str) -> Result<Vec<CommitInfo>>;
 
+  /// Gets the method the server will use to submit (merge) the change and an indicator if the change
+  /// is currently mergeable.
+  ///
+  /// If `other_branches` is set, the mergeability will also be checked for all other branches
+  /// which are listed in the `mergeable_into` field of the response.
+  ///
+  /// As response a `MergeableInfo` entity is returned.
+  ///
+  /// Mergeability cannot be computed for a change that is already merged, in which case the server may
+  /// respond with “409 Conflict”, returned as `Error::Conflict` with the reason from the server.
+  fn get_mergeable(&mut self, change_id: &str, revision_id: &str, other_branches: bool) -> Result<MergeableInfo>;
+
+  /// Gets whether the current revision of the change is mergeable.
+  ///
+  /// The `mergeable` field of `ChangeInfo` is used when the server included it. It is missing
+  /// when the server has not tested the change yet, as older Gerrit versions compute mergeability lazily,
+  /// or when the server is configured to exclude it. Only then `get_mergeable` is called to compute it.
+  fn get_or_compute_mergeable(&mut self, change_id: &str) -> Result<bool>;
+
   /// Retrieves revision actions of the revision of a change.
   ///
   /// The response is a flat map of possible revision actions mapped to their `ActionInfo`.
@@ -516,6 +702,9 @@ pub trait ChangeEndpoints {
   /// As response a `ReviewResult` entity is returned that describes the applied labels and any added reviewers
   /// (e.g. yourself, if you set a label but weren’t previously a reviewer on this CL).
   ///
+  /// The input, including the fix suggestions of robot comments, is validated before sending,
+  /// see `ReviewInput::validate`.
+  ///
   /// It is also possible to add one or more reviewers or CCs to a change simultaneously with a review.
   /// Each element of the reviewers list is an instance of `ReviewerInput`.
   /// The corresponding result of adding each reviewer will be returned in a map of inputs to `AddReviewerResults`.
@@ -548,6 +737,18 @@ pub trait ChangeEndpoints {
   /// contained in the response body.
   fn submit_revision(&mut self, change_id: &str, revision_id: &str) -> Result<SubmitInfo>;
 
+  /// Cherry picks a revision to a destination branch.
+  ///
+  /// The commit message and destination branch must be provided in the request body inside a `CherryPickInput` entity.
+  /// If the commit message does not specify a Change-Id, a new one is picked for the destination change.
+  ///
+  /// As response a `ChangeInfo` entity is returned that describes the resulting cherry-picked change.
+  ///
+  /// If the cherry-pick fails due to conflicts and `allow_conflicts` is not set, the response is
+  /// “409 Conflict” and the error message is contained in the response body.
+  /// With `allow_conflicts` set the change is created anyway, with `contains_git_conflicts` set in the returned `ChangeInfo`.
+  fn cherry_pick(&mut self, change_id: &str, revision_id: &str, input: &CherryPickInput) -> Result<ChangeInfo>;
+
   /// Gets the formatted patch for one revision.
   ///
   /// The formatted patch is returned as text encoded inside base64.
@@ -560,8 +761,21 @@ pub trait ChangeEndpoints {
   /// `commitsha1.diff.base64`, for later processing by command line tools.
   ///
   /// If the path parameter is set, the returned content is a diff of the single file that the path refers to.
+  ///
+  /// As response the plain text patch is returned, already base64 decoded,
+  /// or the raw ZIP archive if the `zip` option is set.
   fn get_patch(&mut self, change_id: &str, revision_id: &str, opts: &Option<PatchParams>) -> Result<Vec<u8>>;
 
+  /// Downloads the formatted patch for one revision and writes it to the file at `path`.
+  ///
+  /// The patch is fetched as in `get_patch`, so the file holds the plain text patch,
+  /// or the ZIP archive if the `zip` option is set.
+  ///
+  /// As response the number of bytes written to the file is returned.
+  fn download_patch(
+    &mut self, change_id: &str, revision_id: &str, opts: &Option<PatchParams>, path: &Path,
+  ) -> Result<usize>;
+
   /// Gets a file containing thin bundles of all modified projects if this change was submitted.
   ///
   /// The bundles are named ${ProjectName}.git. Each thin bundle contains enough to construct the state
@@ -580,7 +794,7 @@ pub trait ChangeEndpoints {
   /// Lists the draft comments of a revision that belong to the calling user.
   ///
   /// Returns a map of file paths to lists of CommentInfo entries. The entries in the map are sorted by file path.
-  fn list_drafts(&mut self, change_id: &str, revision_id: &str) -> Result<BTreeMap<String, CommentInfo>>;
+  fn list_drafts(&mut self, change_id: &str, revision_id: &str) -> Result<BTreeMap<String, Vec<CommentInfo>>>;
 
   /// Creates a draft comment on a revision.
   ///
@@ -596,7 +810,8 @@ pub trait ChangeEndpoints {
 
   /// Updates a draft comment on a revision.
   ///
-  /// The new draft comment must be provided in the request body inside a CommentInput entity.
+  /// The new draft comment must be provided in the request body inside a CommentInput entity,
+  /// with the `id` of the draft to update set, otherwise `Error::InvalidInput` is returned.
   ///
   /// As response a CommentInfo entity is returned that describes the draft comment.
   fn update_draft(&mut self, change_id: &str, revision_id: &str, input: &CommentInput) -> Result<CommentInfo>;
@@ -625,7 +840,7 @@ pub trait ChangeEndpoints {
   ///
   /// Deletion reason can be provided in the request body as a DeleteCommentInput entity.
   /// Historically, this method allowed a body in the DELETE, but that behavior is deprecated.
-  /// In this case, use a POST request instead:
+  /// In this case, use a POST request instead, as done here without a reason.
   fn delete_comment(&mut self, change_id: &str, revision_id: &str, comment_id: &str) -> Result<CommentInfo>;
 
   /// Lists the files that were modified, added or deleted in a revision.
@@ -650,10 +865,19 @@ pub trait ChangeEndpoints {
   /// in this commit compared to the given revision. The revision must correspond to a patch set in the change.
   ///
   /// The reviewed, q, parent, and base options are mutually exclusive. That is, only one of them may be used at a time.
+  ///
+  /// As the reviewed and q options return a list of paths instead, use `list_file_paths` for them.
   fn list_files(
     &mut self, change_id: &str, revision_id: &str, opts: &Option<ListFilesParams>,
   ) -> Result<BTreeMap<String, FileInfo>>;
 
+  /// Lists the paths of the files of a revision selected by the reviewed or q option.
+  ///
+  /// Exactly one of the reviewed and q options of `ListFilesParams` must be set, the others must not.
+  ///
+  /// As response a list of file paths is returned.
+  fn list_file_paths(&mut self, change_id: &str, revision_id: &str, opts: &ListFilesParams) -> Result<Vec<String>>;
+
   /// Gets the content of a file from a certain revision.
   ///
   /// The optional, integer-valued parent parameter can be specified to request the named file from
@@ -668,12 +892,32 @@ pub trait ChangeEndpoints {
   ///
   /// Alternatively, if the only value of the Accept request header is application/json the content is returned as
   /// JSON string and X-FYI-Content-Encoding is set to json.
+  ///
+  /// As response the file content is returned, already base64 decoded.
   fn get_content(
     &mut self, change_id: &str, revision_id: &str, file_id: &str, opts: &Option<GetContentParams>,
   ) -> Result<Vec<u8>>;
 
+  /// Gets the content of several files from a certain revision.
+  ///
+  /// The files are given by their paths, which are URL encoded by this method, and fetched one after
+  /// the other as in `get_content` from the revision itself.
+  ///
+  /// As response a map is returned that maps each path to its decoded content. Paths that do not exist
+  /// in the revision are left out of the map, so that a missing file does not prevent the others from
+  /// being returned. Any other failure to fetch a file is returned as the error.
+  fn get_files_content(
+    &mut self, change_id: &str, revision_id: &str, paths: &[&str],
+  ) -> Result<BTreeMap<String, Vec<u8>>>;
+
   /// Gets the diff of a file from a certain revision.
   ///
+  /// The base parameter can be specified to control the base patch set from which the diff should be generated.
+  ///
+  /// For merge commits, the integer-valued parameter parent can be specified to control the parent commit number
+  /// against which the diff should be generated. The value is the 1-based index of the parent’s position in the
+  /// commit object. If not specified, the diff is generated against the auto merge result.
+  ///
   /// As response a DiffInfo entity is returned that describes the diff.
   fn get_diff(
     &mut self, change_id: &str, revision_id: &str, file_id: &str, opts: &Option<DiffParams>,
@@ -928,6 +1172,121 @@ pub struct ChangeInfo {
   pub revert_of: Option<u32>,
   /// ID of the submission of this change. Only set if the status is MERGED.
   pub submission_id: Option<String>,
+  /// Whether the change contains git conflict markers.
+  /// Only set for changes created by a cherry-pick, rebase or merge that was allowed to have conflicts.
+  #[serde(default)]
+  pub contains_git_conflicts: bool,
+  /// The numeric Change-Id of the change that this change was cherry-picked from.
+  /// Only set if the change is a cherry-pick.
+  pub cherry_pick_of_change: Option<u32>,
+  /// The patch set of the change that this change was cherry-picked from.
+  /// Only set if the change is a cherry-pick.
+  pub cherry_pick_of_patch_set: Option<u32>,
+  /// The SHA-1 of the NoteDb meta ref of the change, as returned by newer Gerrit versions.
+  pub meta_rev_id: Option<String>,
+  /// The numeric ID of the change used by the server for this change,
+  /// which differs from `number` for changes imported from other servers. Only returned by newer Gerrit versions.
+  pub virtual_id_number: Option<u32>,
+}
+
+impl ChangeInfo {
+  /// Whether the change is owned by the account with the given numeric ID.
+  pub fn is_owned_by(&self, account_id: u32) -> bool {
+    self.owner.account_id == account_id
+  }
+
+  /// Whether the change was submitted by the account with the given numeric ID.
+  pub fn is_submitted_by(&self, account_id: u32) -> bool {
+    self.submitter.as_ref().map(|submitter| submitter.account_id) == Some(account_id)
+  }
+
+  /// Whether the change is marked as Work In Progress.
+  pub fn is_wip(&self) -> bool {
+    self.work_in_progress
+  }
+
+  /// Whether the change is marked as private.
+  pub fn is_private(&self) -> bool {
+    self.is_private
+  }
+
+  /// Reconstruct the history of state changes per reviewer from the reviewer updates.
+  ///
+  /// Empty if reviewer updates were not requested.
+  pub fn reviewer_history(&self) -> Vec<ReviewerHistory> {
+    self
+      .reviewer_updates
+      .as_ref()
+      .map(|updates| ReviewerHistory::from_updates(updates))
+      .unwrap_or_default()
+  }
+
+  /// Get the combined vote on the given label.
+  ///
+  /// Only available if labels or detailed labels were requested. See `LabelInfo::combined_vote`.
+  pub fn label_vote(&self, label: &str) -> Option<i32> {
+    self.labels.as_ref()?.get(label)?.combined_vote()
+  }
+
+  /// Get the ids this change is tracked by in the given external tracking system, e.g. `"JIRA"`.
+  ///
+  /// Empty if tracking ids were not requested.
+  pub fn tracking_ids_for_system(&self, system: &str) -> Vec<&str> {
+    self
+      .tracking_ids
+      .iter()
+      .flatten()
+      .filter(|tracking| tracking.system == system)
+      .map(|tracking| tracking.id.as_str())
+      .collect()
+  }
+
+  /// Get a one-line summary of the change with its number, status, subject and owner,
+  /// e.g. `12345  MERGED  Fix the thing  (alice)`.
+  ///
+  /// The owner is given by name, username or email, whichever is available first, or else by account ID.
+  pub fn summary_line(&self) -> String {
+    let owner = self
+      .owner
+      .name
+      .as_ref()
+      .or(self.owner.username.as_ref())
+      .or(self.owner.email.as_ref());
+    let owner = match owner {
+      Some(owner) => owner.clone(),
+      None => self.owner.account_id.to_string(),
+    };
+    format!("{}  {}  {}  ({})", self.number, self.status, self.subject, owner)
+  }
+}
+
+impl Display for ChangeInfo {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), Error> {
+    f.write_str(&self.summary_line())
+  }
+}
+
+/// Keep only the changes whose combined vote on `label` is at least `min`.
+///
+/// Changes without the label, or without a known vote on it, are filtered out.
+pub fn filter_by_label(changes: Vec<ChangeInfo>, label: &str, min: i32) -> Vec<ChangeInfo> {
+  changes
+    .into_iter()
+    .filter(|change| matches!(change.label_vote(label), Some(vote) if vote >= min))
+    .collect()
+}
+
+/// The ChangeInfoDifference entity contains the difference between two ChangeInfo entities.
+///
+/// Only the fields that differ are present, so the partial entities are kept as raw JSON values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeInfoDifference {
+  /// Fields of ChangeInfo that are present in the newer state but not in the older one,
+  /// or that differ between both states, with their new values.
+  pub added: serde_json::Value,
+  /// Fields of ChangeInfo that are present in the older state but not in the newer one,
+  /// or that differ between both states, with their old values.
+  pub removed: serde_json::Value,
 }
 
 /// The ChangeInput entity contains information about creating a new change.
@@ -975,6 +1334,11 @@ pub struct ChangeInput {
   /// Additional information about whom to notify about the update as a
   /// map of recipient type to NotifyInfo entity.
   pub notify_details: Option<HashMap<RecipientType, NotifyInfo>>,
+  /// Map with key-value pairs that are forwarded as options to the commit validation listeners
+  /// (e.g. can be used to skip certain validations). Which validation options are supported depends
+  /// on the installed commit validation listeners. Gerrit core doesn’t support any validation options,
+  /// but commit validation listeners that are implemented in plugins may.
+  pub validation_options: Option<HashMap<String, String>>,
 }
 
 /// Change kind.
@@ -1019,13 +1383,30 @@ pub struct ChangeMessageInfo {
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 pub enum ChangeStatus {
+  /// The change is open.
   New,
+  /// The change has been merged.
   Merged,
+  /// The change has been submitted but is not yet merged.
   Submitted,
+  /// The change has been abandoned.
   Abandoned,
+  /// The change is a draft.
+  ///
+  /// Draft changes were removed in Gerrit 2.15 in favor of work-in-progress and private changes.
+  /// The status is still parsed for responses of older servers.
   Draft,
 }
 
+impl ChangeStatus {
+  /// Whether this status is no longer returned by recent Gerrit versions.
+  ///
+  /// Only `Draft` is deprecated; use the `work_in_progress` and `is_private` fields of `ChangeInfo` instead.
+  pub fn is_deprecated(&self) -> bool {
+    *self == ChangeStatus::Draft
+  }
+}
+
 /// The type of change.
 #[derive(Debug, Clone, Display, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -1133,7 +1514,7 @@ pub struct CommentInput {
   pub in_reply_to: Option<String>,
   /// The timestamp of when this comment was written.
   /// Accepted but ignored.
-  pub updated: Timestamp,
+  pub updated: Option<Timestamp>,
   /// The comment message.
   /// If not set and an existing draft comment is updated, the existing draft comment is deleted.
   pub message: Option<String>,
@@ -1154,7 +1535,7 @@ pub struct CommentInput {
 /// So, a range over part of a line will have start_line equal to end_line;
 /// however a range with end_line set to 5 and end_character equal to 0 will not include any
 /// characters on line 5,
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CommentRange {
   /// The start line number of the range. (1-based)
   pub start_line: u32,
@@ -1166,6 +1547,30 @@ pub struct CommentRange {
   pub end_character: u32,
 }
 
+impl CommentRange {
+  fn start(&self) -> (u32, u32) {
+    (self.start_line, self.start_character)
+  }
+
+  fn end(&self) -> (u32, u32) {
+    (self.end_line, self.end_character)
+  }
+
+  /// Whether the position at `line` (1-based) and `character` (0-based) lies within this range.
+  ///
+  /// The start position is inclusive and the end position is exclusive.
+  pub fn contains(&self, line: u32, character: u32) -> bool {
+    self.start() <= (line, character) && (line, character) < self.end()
+  }
+
+  /// Whether this range shares at least one character with `other`.
+  ///
+  /// Adjacent ranges, where one ends exactly where the other starts, do not overlap.
+  pub fn overlaps(&self, other: &CommentRange) -> bool {
+    self.start() < other.end() && other.start() < self.end()
+  }
+}
+
 /// The CommitInfo entity contains information about a commit.
 #[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1272,19 +1677,19 @@ pub struct DescriptionInput {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiffContent {
   /// Content only in the file on side A (deleted in B).
-  pub a: Option<String>,
+  pub a: Option<Vec<String>>,
   /// Content only in the file on side B (added in B).
-  pub b: Option<String>,
+  pub b: Option<Vec<String>>,
   /// Content in the file on both sides (unchanged).
-  pub ab: Option<String>,
+  pub ab: Option<Vec<String>>,
   /// Text sections deleted from side A as a DiffIntralineInfo entity.
   /// Only present when the intraline parameter is set and the DiffContent is a replace,
   /// i.e. both a and b are present
-  pub edit_a: Option<String>,
+  pub edit_a: Option<DiffIntralineInfo>,
   /// Text sections inserted in side B as a DiffIntralineInfo entity.
   /// Only present when the intraline parameter is set and the DiffContent is a replace,
   /// i.e. both a and b are present
-  pub edit_b: Option<String>,
+  pub edit_b: Option<DiffIntralineInfo>,
   /// Indicates whether this entry was introduced by a rebase.
   #[serde(default)]
   pub due_to_rebase: bool,
@@ -1330,7 +1735,7 @@ pub struct DiffInfo {
   /// The content differences in the file as a list of DiffContent entities.
   pub content: Vec<DiffContent>,
   /// Links to the file diff in external sites as a list of DiffWebLinkInfo entries.
-  pub web_links: Option<DiffWebLinkInfo>,
+  pub web_links: Option<Vec<DiffWebLinkInfo>>,
   /// Whether the file is binary.
   #[serde(default)]
   pub binary: bool,
@@ -1346,10 +1751,11 @@ pub struct DiffInfo {
 ///
 /// Note that the implied newline character at the end of each line is included in the
 /// length calculation,and thus it is possible for the edits to span newlines.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct DiffIntralineInfo {
-  #[serde(flatten)]
-  pub values: Vec<String>,
+  /// The list of <skip length, edit length> pairs.
+  pub edits: Vec<(u32, u32)>,
 }
 
 /// The DiffWebLinkInfo entity describes a link on a diff screen to an external site.
@@ -1382,7 +1788,7 @@ pub enum DraftHandling {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EditFileInfo {
   /// Links to the diff info in external sites as a list of WebLinkInfo entities.
-  pub wbe_links: Option<Vec<WebLinkInfo>>,
+  pub web_links: Option<Vec<WebLinkInfo>>,
 }
 
 /// The EditInfo entity contains information about a change edit.
@@ -1394,7 +1800,7 @@ pub struct EditInfo {
   /// The patch set number of the patch set the change edit is based on.
   pub base_patch_set_number: u32,
   /// The revision of the patch set the change edit is based on.
-  pub base_revision: u32,
+  pub base_revision: String,
   /// The ref of the change edit.
   #[serde(rename = "ref")]
   pub refspec: String,
@@ -1449,9 +1855,10 @@ pub struct FileInfo {
 }
 
 /// File status.
-#[derive(Debug, Display, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[derive(Debug, Display, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
 #[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 pub enum FileStatus {
+  #[default]
   #[serde(rename = "M")]
   Modified,
   #[serde(rename = "A")]
@@ -1466,17 +1873,12 @@ pub enum FileStatus {
   Rewritten,
 }
 
-impl Default for FileStatus {
-  fn default() -> Self {
-    FileStatus::Modified
-  }
-}
-
 /// The FixInput entity contains options for fixing commits using the fix change endpoint.
 #[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FixInput {
   /// If true, delete patch sets from the database if they refer to missing commit options.
+  #[serde(default)]
   pub delete_patch_set_if_commit_missing: bool,
   /// If set, check that the change is merged into the destination branch as this exact SHA-1.
   /// If not, insert a new patch set referring to this commit.
@@ -1497,6 +1899,34 @@ pub struct FixSuggestionInfo {
   pub replacements: Vec<FixReplacementInfo>,
 }
 
+impl FixSuggestionInfo {
+  /// Check that the replacements are well formed and refer to non-overlapping regions within each file.
+  ///
+  /// Fails with `Error::WrongQuery` describing the first malformed or overlapping replacement.
+  pub fn validate(&self) -> Result<()> {
+    let invalid = |msg: String| Err(crate::error::Error::WrongQuery(msg));
+    if self.replacements.is_empty() {
+      return invalid(format!("fix suggestion '{}' has no replacements", self.description));
+    }
+    for (index, replacement) in self.replacements.iter().enumerate() {
+      let range = &replacement.range;
+      if range.start() > range.end() {
+        return invalid(format!(
+          "replacement range in '{}' ends before it starts",
+          replacement.path
+        ));
+      }
+      let overlapping = self.replacements[..index]
+        .iter()
+        .any(|other| other.path == replacement.path && other.range.overlaps(range));
+      if overlapping {
+        return invalid(format!("replacement ranges in '{}' overlap", replacement.path));
+      }
+    }
+    Ok(())
+  }
+}
+
 /// The FixReplacementInfo entity describes how the content of a file should be replaced by another content.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FixReplacementInfo {
@@ -1524,15 +1954,6 @@ pub struct GitPersonInfo {
   pub tz: i32,
 }
 
-/// The GroupBaseInfo entity contains base information about the group.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GroupBaseInfo {
-  /// The UUID of the group.
-  pub id: String,
-  /// The name of the group.
-  pub name: String,
-}
-
 /// The HashtagsInput entity contains information about hashtags to add to, and/or remove from, a change.
 #[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1543,6 +1964,39 @@ pub struct HashtagsInput {
   pub remove: Option<Vec<String>>,
 }
 
+impl HashtagsInput {
+  /// Get a copy of the input with the hashtags trimmed and the duplicates in `add` removed.
+  ///
+  /// Fails with `Error::WrongQuery` if any hashtag is empty after trimming or contains a comma,
+  /// which the server rejects.
+  pub fn normalized(&self) -> Result<Self> {
+    fn normalize(hashtags: &Option<Vec<String>>, dedup: bool) -> Result<Option<Vec<String>>> {
+      let hashtags = match hashtags {
+        Some(hashtags) => hashtags,
+        None => return Ok(None),
+      };
+      let mut normalized: Vec<String> = Vec::with_capacity(hashtags.len());
+      for hashtag in hashtags {
+        let hashtag = hashtag.trim();
+        if hashtag.is_empty() || hashtag.contains(',') {
+          return Err(crate::error::Error::WrongQuery(format!(
+            "invalid hashtag '{}': hashtags must be non-empty and cannot contain commas",
+            hashtag
+          )));
+        }
+        if !dedup || !normalized.iter().any(|other| other == hashtag) {
+          normalized.push(hashtag.into());
+        }
+      }
+      Ok(Some(normalized))
+    }
+    Ok(Self {
+      add: normalize(&self.add, true)?,
+      remove: normalize(&self.remove, false)?,
+    })
+  }
+}
+
 /// Common HTTP methods to cause state changes.
 #[derive(Debug, Display, PartialEq, Eq, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -1617,6 +2071,52 @@ pub struct LabelInfo {
   pub values: Option<HashMap<String, String>>,
 }
 
+impl LabelInfo {
+  /// Get the combined vote on the label, calculated in the following order (from highest to lowest):
+  /// REJECTED > APPROVED > DISLIKED > RECOMMENDED.
+  ///
+  /// With detailed labels the vote is computed from the approvals of all reviewers,
+  /// and is zero if nobody voted. Otherwise the vote is only known for recommended and disliked labels,
+  /// and for approved and rejected labels it is not available.
+  pub fn combined_vote(&self) -> Option<i32> {
+    if let Some(all) = &self.all {
+      let votes: Vec<i32> = all.iter().filter_map(|approval| approval.value).collect();
+      let min = votes.iter().copied().min().unwrap_or(0);
+      let max = votes.iter().copied().max().unwrap_or(0);
+      let negative = self.rejected.is_some() || (self.approved.is_none() && self.disliked.is_some());
+      return Some(if negative { min } else { max });
+    }
+    if self.rejected.is_some() || self.approved.is_some() {
+      None
+    } else if self.disliked.is_some() {
+      Some(self.value.unwrap_or(-1))
+    } else if self.recommended.is_some() {
+      Some(self.value.unwrap_or(1))
+    } else {
+      Some(0)
+    }
+  }
+}
+
+/// A vote on a label, e.g. `Code-Review` +2.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelVote {
+  /// The name of the label.
+  pub label: String,
+  /// The voting value.
+  pub value: i32,
+}
+
+impl LabelVote {
+  /// Vote `value` on the label `label`.
+  pub fn new(label: &str, value: i32) -> Self {
+    Self {
+      label: label.into(),
+      value,
+    }
+  }
+}
+
 /// The MergeableInfo entity contains information about the mergeability of a change.
 #[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1721,6 +2221,87 @@ pub struct NotifyInfo {
   pub accounts: Option<Vec<String>>,
 }
 
+/// The patch set number of a revision.
+///
+/// Gerrit serializes the number of a pending change edit as the literal string "edit".
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PatchSetNumber {
+  /// A regular patch set number.
+  Number(u32),
+  /// The change edit.
+  Edit,
+}
+
+impl Display for PatchSetNumber {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), Error> {
+    match self {
+      PatchSetNumber::Number(n) => write!(f, "{}", n),
+      PatchSetNumber::Edit => f.write_str("edit"),
+    }
+  }
+}
+
+impl serde::Serialize for PatchSetNumber {
+  fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    match self {
+      PatchSetNumber::Number(n) => serializer.serialize_u32(*n),
+      PatchSetNumber::Edit => serializer.serialize_str("edit"),
+    }
+  }
+}
+
+impl<'de> serde::Deserialize<'de> for PatchSetNumber {
+  fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+  where
+    D: serde::Deserializer<'de>,
+  {
+    struct PatchSetNumberVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for PatchSetNumberVisitor {
+      type Value = PatchSetNumber;
+
+      fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str("a patch set number or \"edit\"")
+      }
+
+      fn visit_u64<E>(self, value: u64) -> std::result::Result<Self::Value, E>
+      where
+        E: serde::de::Error,
+      {
+        if value > u64::from(u32::MAX) {
+          return Err(E::invalid_value(serde::de::Unexpected::Unsigned(value), &self));
+        }
+        Ok(PatchSetNumber::Number(value as u32))
+      }
+
+      fn visit_i64<E>(self, value: i64) -> std::result::Result<Self::Value, E>
+      where
+        E: serde::de::Error,
+      {
+        if value < 0 {
+          return Err(E::invalid_value(serde::de::Unexpected::Signed(value), &self));
+        }
+        self.visit_u64(value as u64)
+      }
+
+      fn visit_str<E>(self, value: &str) -> std::result::Result<Self::Value, E>
+      where
+        E: serde::de::Error,
+      {
+        match value {
+          "edit" => Ok(PatchSetNumber::Edit),
+          _ => Err(E::invalid_value(serde::de::Unexpected::Str(value), &self)),
+        }
+      }
+    }
+
+    deserializer.deserialize_any(PatchSetNumberVisitor)
+  }
+}
+
 /// The PrivateInput entity contains information for changing the private flag on a change.
 #[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1837,6 +2418,19 @@ pub struct RelatedChangeAndCommitInfo {
   pub status: Option<ChangeStatus>,
 }
 
+impl RelatedChangeAndCommitInfo {
+  /// Whether the related change is based on an outdated patch set of it,
+  /// i.e. its revision number is behind its current revision number.
+  ///
+  /// Returns None if the revision numbers are not known, e.g. for a commit that is not a change.
+  pub fn is_outdated(&self) -> Option<bool> {
+    match (self.revision_number, self.current_revision_number) {
+      (Some(revision), Some(current)) => Some(revision < current),
+      _ => None,
+    }
+  }
+}
+
 /// The RelatedChangesInfo entity contains information about related changes.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RelatedChangesInfo {
@@ -1845,6 +2439,37 @@ pub struct RelatedChangesInfo {
   pub changes: Vec<RelatedChangeAndCommitInfo>,
 }
 
+impl RelatedChangesInfo {
+  /// Get the position of the change with the given number in the relation chain,
+  /// where 0 is the newest change.
+  pub fn position_of(&self, change_number: u32) -> Option<usize> {
+    self
+      .changes
+      .iter()
+      .position(|change| change.change_number == Some(change_number))
+  }
+
+  /// Get the changes and commits the given change is based on, newest to oldest.
+  ///
+  /// Returns an empty list if the change is not part of the relation chain.
+  pub fn ancestors(&self, change_number: u32) -> &[RelatedChangeAndCommitInfo] {
+    match self.position_of(change_number) {
+      Some(position) => &self.changes[position + 1..],
+      None => &[],
+    }
+  }
+
+  /// Get the changes that are based on the given change, newest to oldest.
+  ///
+  /// Returns an empty list if the change is not part of the relation chain.
+  pub fn descendants(&self, change_number: u32) -> &[RelatedChangeAndCommitInfo] {
+    match self.position_of(change_number) {
+      Some(position) => &self.changes[..position],
+      None => &[],
+    }
+  }
+}
+
 /// The Requirement entity contains information about a requirement relative to a change.
 #[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1941,6 +2566,59 @@ pub struct ReviewerUpdateInfo {
   pub state: ReviewerState,
 }
 
+/// The history of state changes of a single reviewer of a change,
+/// reconstructed from the change's ReviewerUpdateInfo entities.
+#[derive(Debug, Clone)]
+pub struct ReviewerHistory {
+  /// The reviewer account.
+  pub reviewer: AccountInfo,
+  /// The state changes of the reviewer, sorted from oldest to newest.
+  pub changes: Vec<ReviewerStateChange>,
+}
+
+/// A single state change of a reviewer.
+#[derive(Debug, Clone)]
+pub struct ReviewerStateChange {
+  /// Timestamp of the update.
+  pub updated: Timestamp,
+  /// The account which modified state of the reviewer.
+  pub updated_by: AccountInfo,
+  /// The reviewer state after the update.
+  pub state: ReviewerState,
+}
+
+impl ReviewerHistory {
+  /// Group reviewer updates per reviewer, in chronological order.
+  ///
+  /// The returned list is ordered by the time each reviewer was first updated.
+  pub fn from_updates(updates: &[ReviewerUpdateInfo]) -> Vec<ReviewerHistory> {
+    let mut sorted: Vec<&ReviewerUpdateInfo> = updates.iter().collect();
+    sorted.sort_by_key(|update| update.updated.0);
+    let mut histories: Vec<ReviewerHistory> = Vec::new();
+    let mut index: HashMap<u32, usize> = HashMap::new();
+    for update in sorted {
+      let i = *index.entry(update.reviewer.account_id).or_insert_with(|| {
+        histories.push(ReviewerHistory {
+          reviewer: update.reviewer.clone(),
+          changes: Vec::new(),
+        });
+        histories.len() - 1
+      });
+      histories[i].changes.push(ReviewerStateChange {
+        updated: update.updated.clone(),
+        updated_by: update.updated_by.clone(),
+        state: update.state.clone(),
+      });
+    }
+    histories
+  }
+
+  /// The current state of the reviewer, which is the state of the latest update.
+  pub fn current_state(&self) -> Option<&ReviewerState> {
+    self.changes.last().map(|change| &change.state)
+  }
+}
+
 /// The ReviewInput entity contains information for adding a review to a revision.
 #[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1985,18 +2663,171 @@ pub struct ReviewInput {
   pub work_in_progress: Option<bool>,
 }
 
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ReviewResult {
-  /// Map of labels to values after the review was posted.
-  /// `Null` if any reviewer additions were rejected.
-  pub labels: Option<BTreeMap<String, i32>>,
-  /// Map of account or group identifier to `AddReviewerResult` representing the outcome of adding as a reviewer.
-  /// Absent if no reviewer additions were requested.
-  pub reviewers: Option<BTreeMap<String, AddReviewerResult>>,
+impl ReviewInput {
+  /// Remove inline comments that duplicate an earlier one on the same file, keeping the first occurrence.
+  ///
+  /// Comments are duplicates if they have the same path, line, range and message.
+  /// This is a client-side counterpart of `omit_duplicate_comments`, which only compares against
+  /// comments already stored on the server.
+  pub fn dedup_comments(&mut self) {
+    for (path, comments) in self.comments.iter_mut().flatten() {
+      let mut kept: Vec<CommentInput> = Vec::with_capacity(comments.len());
+      for comment in comments.drain(..) {
+        let duplicate = kept.iter().any(|other| {
+          other.path.as_ref().unwrap_or(path) == comment.path.as_ref().unwrap_or(path)
+            && other.line == comment.line
+            && other.range == comment.range
+            && other.message == comment.message
+        });
+        if !duplicate {
+          kept.push(comment);
+        }
+      }
+      *comments = kept;
+    }
+  }
+
+  /// Check for option combinations that the server rejects.
+  ///
+  /// Only `DraftHandling::Keep` is allowed together with `on_behalf_of`,
+  /// and a change cannot be set both ready and work in progress.
+  /// The fix suggestions of robot comments are checked with `FixSuggestionInfo::validate`.
+  /// Fails with `Error::WrongQuery` describing the first invalid combination.
+  pub fn validate(&self) -> Result<()> {
+    let invalid = |msg: &str| Err(crate::error::Error::WrongQuery(msg.into()));
+    let keep_drafts = matches!(self.drafts, None | Some(DraftHandling::Keep));
+    if self.on_behalf_of.is_some() && !keep_drafts {
+      return invalid("only KEEP drafts handling is allowed together with on_behalf_of");
+    }
+    if self.ready == Some(true) && self.work_in_progress == Some(true) {
+      return invalid("ready and work_in_progress cannot both be set");
+    }
+    for robot_comment in self.robot_comments.iter().flat_map(|map| map.values().flatten()) {
+      for fix in robot_comment.fix_suggestions.iter().flatten() {
+        fix.validate()?;
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Builder for a `ReviewInput` with a message, label votes and inline comments.
+#[derive(Debug, Clone)]
+pub struct ReviewInputBuilder {
+  input: ReviewInput,
+}
+
+impl Default for ReviewInputBuilder {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl ReviewInputBuilder {
+  /// Start an empty review.
+  pub fn new() -> Self {
+    Self {
+      input: ReviewInput {
+        message: None,
+        tag: None,
+        labels: None,
+        comments: None,
+        robot_comments: None,
+        drafts: None,
+        notify: None,
+        notify_details: None,
+        omit_duplicate_comments: None,
+        on_behalf_of: None,
+        reviewers: None,
+        ready: None,
+        work_in_progress: None,
+      },
+    }
+  }
+
+  /// Set the message to be added as review comment.
+  pub fn message(mut self, message: &str) -> Self {
+    self.input.message = Some(message.into());
+    self
+  }
+
+  /// Vote `value` on the label `name`, replacing any previous vote on it.
+  pub fn label(mut self, name: &str, value: i32) -> Self {
+    self
+      .input
+      .labels
+      .get_or_insert_with(BTreeMap::new)
+      .insert(name.into(), value);
+    self
+  }
+
+  /// Add the given votes, replacing any previous vote on the same labels.
+  pub fn votes(mut self, votes: Votes) -> Self {
+    self
+      .input
+      .labels
+      .get_or_insert_with(BTreeMap::new)
+      .extend(BTreeMap::from(votes));
+    self
+  }
+
+  /// Add an inline comment on the file `path`.
+  pub fn comment(mut self, path: &str, comment: CommentInput) -> Self {
+    self
+      .input
+      .comments
+      .get_or_insert_with(HashMap::new)
+      .entry(path.into())
+      .or_default()
+      .push(comment);
+    self
+  }
+
+  /// Set the tag to apply to the review comment message, votes, and inline comments.
+  pub fn tag(mut self, tag: &str) -> Self {
+    self.input.tag = Some(tag.into());
+    self
+  }
+
+  /// Set to whom email notifications should be sent after the review is stored.
+  pub fn notify(mut self, notify: NotifyHandling) -> Self {
+    self.input.notify = Some(notify);
+    self
+  }
+
+  /// Return the assembled `ReviewInput`.
+  pub fn build(self) -> ReviewInput {
+    self.input
+  }
+}
+
+/// The ReviewResult entity contains information regarding the updates that were made to a review.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewResult {
+  /// Map of labels to values after the review was posted.
+  /// `Null` if any reviewer additions were rejected.
+  pub labels: Option<BTreeMap<String, i32>>,
+  /// Map of account or group identifier to `AddReviewerResult` representing the outcome of adding as a reviewer.
+  /// Absent if no reviewer additions were requested.
+  pub reviewers: Option<BTreeMap<String, AddReviewerResult>>,
   /// If true, the change was moved from WIP to ready for review as a result of this action.
   #[serde(default)]
   pub ready: bool,
+  /// Error message for non-200 responses.
+  pub error: Option<String>,
+}
+
+impl ReviewResult {
+  /// Get the reviewer additions that failed, as pairs of the reviewer input and its error message.
+  pub fn failed_reviewers(&self) -> Vec<(&str, &str)> {
+    self
+      .reviewers
+      .iter()
+      .flatten()
+      .filter_map(|(input, result)| result.error.as_deref().map(|error| (input.as_str(), error)))
+      .collect()
+  }
 }
 
 /// The ReviewerInfo entity contains information about a reviewer and its votes on a change.
@@ -2037,6 +2868,39 @@ pub struct ReviewerInput {
   pub notify_details: Option<HashMap<RecipientType, NotifyInfo>>,
 }
 
+impl ReviewerInput {
+  /// Add the account or group with the given ID as reviewer, with the default options.
+  pub fn new(reviewer: &str) -> Self {
+    Self {
+      reviewer: reviewer.into(),
+      state: None,
+      confirmed: None,
+      notify: None,
+      notify_details: None,
+    }
+  }
+
+  /// Add the account or group with the given ID as CC.
+  pub fn cc(reviewer: &str) -> Self {
+    Self {
+      state: Some(ReviewerState::Cc),
+      ..Self::new(reviewer)
+    }
+  }
+}
+
+impl From<&str> for ReviewerInput {
+  fn from(reviewer: &str) -> Self {
+    Self::new(reviewer)
+  }
+}
+
+impl From<String> for ReviewerInput {
+  fn from(reviewer: String) -> Self {
+    Self::new(&reviewer)
+  }
+}
+
 /// The ReviewerInput entity contains information for adding a reviewer to a change.
 #[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -2044,7 +2908,7 @@ pub struct RevisionInfo {
   /// The change kind.
   pub kind: Option<ChangeKind>,
   /// The patch set number, or edit if the patch set is an edit.
-  pub _number: u32,
+  pub _number: PatchSetNumber,
   /// The timestamp of when the patch set was created.
   pub created: Option<Timestamp>,
   /// The uploader of the patch set as an AccountInfo entity.
@@ -2056,6 +2920,7 @@ pub struct RevisionInfo {
   /// The fetch information is provided as a map that maps the protocol name (“git”, “http”, “ssh”)
   /// to FetchInfo entities. This information is only included if a plugin implementing the
   /// download commands interface is installed.
+  #[serde(default)]
   pub fetch: HashMap<String, FetchInfo>,
   /// The commit of the patch set as CommitInfo entity.
   pub commit: Option<CommitInfo>,
@@ -2082,6 +2947,27 @@ pub struct RevisionInfo {
   pub description: Option<String>,
 }
 
+impl RevisionInfo {
+  /// Protocols in order of preference when picking how to fetch a patch set.
+  const FETCH_PROTOCOL_PREFERENCE: [&str; 3] = ["ssh", "http", "git"];
+
+  /// Get the download command named `command` for fetching this patch set via `protocol`.
+  ///
+  /// Only available if download commands were requested.
+  pub fn fetch_command(&self, protocol: &str, command: &str) -> Option<String> {
+    self.fetch.get(protocol)?.commands.as_ref()?.get(command).cloned()
+  }
+
+  /// Get the preferred protocol and its FetchInfo for fetching this patch set.
+  ///
+  /// SSH is preferred over HTTP, and HTTP over Git.
+  pub fn preferred_fetch(&self) -> Option<(&str, &FetchInfo)> {
+    Self::FETCH_PROTOCOL_PREFERENCE
+      .iter()
+      .find_map(|protocol| self.fetch.get(*protocol).map(|fetch| (*protocol, fetch)))
+  }
+}
+
 /// The RobotCommentInfo entity contains information about a robot inline comment.
 /// RobotCommentInfo has the same fields as CommentInfo. In addition RobotCommentInfo has the following fields:
 #[skip_serializing_none]
@@ -2103,12 +2989,141 @@ pub struct RobotCommentInfo {
 }
 
 /// The RobotCommentInput entity contains information for creating an inline robot comment.
-/// RobotCommentInput has the same fields as RobotCommentInfo.
+/// RobotCommentInput has the same fields as CommentInput. In addition RobotCommentInput has the following fields:
+#[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RobotCommentInput {
-  /// The robot comment information entity.
+  /// The comment input entity.
   #[serde(flatten)]
-  pub inner: RobotCommentInfo,
+  pub comment: CommentInput,
+  /// The ID of the robot that generated this comment.
+  pub robot_id: String,
+  /// An ID of the run of the robot.
+  pub robot_run_id: String,
+  /// URL to more information.
+  pub url: Option<String>,
+  /// Robot specific properties as map that maps arbitrary keys to values.
+  pub properties: Option<HashMap<String, String>>,
+  /// Suggested fixes for this robot comment as a list of FixSuggestionInfo entities.
+  pub fix_suggestions: Option<Vec<FixSuggestionInfo>>,
+}
+
+/// Builder for a `RobotCommentInput` that can be attached to a `ReviewInput`.
+///
+/// Intended for linters and other automated tools that post findings with suggested fixes.
+#[derive(Debug, Clone)]
+pub struct RobotCommentBuilder {
+  path: String,
+  input: RobotCommentInput,
+}
+
+impl RobotCommentBuilder {
+  /// Start a robot comment on file `path` from robot `robot_id` in run `robot_run_id`.
+  pub fn new(path: &str, robot_id: &str, robot_run_id: &str, message: &str) -> Self {
+    Self {
+      path: path.into(),
+      input: RobotCommentInput {
+        comment: CommentInput {
+          id: None,
+          path: None,
+          side: None,
+          line: None,
+          range: None,
+          in_reply_to: None,
+          updated: None,
+          message: Some(message.into()),
+          tag: None,
+          unresolved: None,
+        },
+        robot_id: robot_id.into(),
+        robot_run_id: robot_run_id.into(),
+        url: None,
+        properties: None,
+        fix_suggestions: None,
+      },
+    }
+  }
+
+  /// Place the comment on a single line.
+  pub fn line(mut self, line: u32) -> Self {
+    self.input.comment.line = Some(line);
+    self
+  }
+
+  /// Place the comment on a range of the file.
+  pub fn range(mut self, range: CommentRange) -> Self {
+    self.input.comment.range = Some(range);
+    self
+  }
+
+  /// Set the side of the file the comment applies to.
+  pub fn side(mut self, side: CommentSide) -> Self {
+    self.input.comment.side = Some(side);
+    self
+  }
+
+  /// Mark whether the comment must be addressed by the user.
+  pub fn unresolved(mut self, unresolved: bool) -> Self {
+    self.input.comment.unresolved = Some(unresolved);
+    self
+  }
+
+  /// Set the URL to more information about the finding.
+  pub fn url(mut self, url: &str) -> Self {
+    self.input.url = Some(url.into());
+    self
+  }
+
+  /// Add a robot specific property.
+  pub fn property(mut self, key: &str, value: &str) -> Self {
+    self
+      .input
+      .properties
+      .get_or_insert_with(HashMap::new)
+      .insert(key.into(), value.into());
+    self
+  }
+
+  /// Add a suggested fix made of the given replacements.
+  pub fn fix(mut self, description: &str, replacements: Vec<FixReplacementInfo>) -> Self {
+    self
+      .input
+      .fix_suggestions
+      .get_or_insert_with(Vec::new)
+      .push(FixSuggestionInfo {
+        fix_id: None,
+        description: description.into(),
+        replacements,
+      });
+    self
+  }
+
+  /// Validate and return the file path together with the assembled `RobotCommentInput`.
+  pub fn build(self) -> Result<(String, RobotCommentInput)> {
+    let invalid = |msg: String| Err(crate::error::Error::InvalidInput(msg));
+    if self.path.is_empty() {
+      return invalid("robot comment path must not be empty".into());
+    }
+    if self.input.robot_id.is_empty() || self.input.robot_run_id.is_empty() {
+      return invalid("robot comment requires robot_id and robot_run_id".into());
+    }
+    for fix in self.input.fix_suggestions.iter().flatten() {
+      fix.validate()?;
+    }
+    Ok((self.path, self.input))
+  }
+
+  /// Validate the robot comment and add it to the robot comments of `review`.
+  pub fn attach_to(self, review: &mut ReviewInput) -> Result<()> {
+    let (path, input) = self.build()?;
+    review
+      .robot_comments
+      .get_or_insert_with(HashMap::new)
+      .entry(path)
+      .or_default()
+      .push(input);
+    Ok(())
+  }
 }
 
 /// The RuleInput entity contains information to test a Prolog rule.
@@ -2284,6 +3299,54 @@ pub struct VotingRangeInfo {
   pub max: i32,
 }
 
+/// A list of label votes.
+///
+/// Serialized as the map of label names to voting values used by the REST API, and convertible to and from it.
+/// A later vote on a label replaces an earlier one when converted to a map.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<String, i32>", into = "BTreeMap<String, i32>")]
+pub struct Votes(pub Vec<LabelVote>);
+
+impl Votes {
+  /// Start an empty list of votes.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// A single vote on the `Code-Review` label.
+  pub fn code_review(value: i32) -> Self {
+    Self::new().vote("Code-Review", value)
+  }
+
+  /// A single vote on the `Verified` label.
+  pub fn verified(value: i32) -> Self {
+    Self::new().vote("Verified", value)
+  }
+
+  /// Add a vote `value` on the label `label`.
+  pub fn vote(mut self, label: &str, value: i32) -> Self {
+    self.0.push(LabelVote::new(label, value));
+    self
+  }
+}
+
+impl From<BTreeMap<String, i32>> for Votes {
+  fn from(map: BTreeMap<String, i32>) -> Self {
+    Self(
+      map
+        .into_iter()
+        .map(|(label, value)| LabelVote { label, value })
+        .collect(),
+    )
+  }
+}
+
+impl From<Votes> for BTreeMap<String, i32> {
+  fn from(votes: Votes) -> Self {
+    votes.0.into_iter().map(|vote| (vote.label, vote.value)).collect()
+  }
+}
+
 /// The WebLinkInfo entity describes a link to an external site.
 #[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -2296,6 +3359,44 @@ pub struct WebLinkInfo {
   pub image_url: Option<String>,
 }
 
+/// Common interface of the web link entities, to render any kind of link uniformly.
+pub trait WebLink {
+  /// The link name.
+  fn name(&self) -> &str;
+  /// The link URL.
+  fn url(&self) -> &str;
+  /// URL to the icon of the link, if any.
+  fn image_url(&self) -> Option<&str>;
+}
+
+impl WebLink for WebLinkInfo {
+  fn name(&self) -> &str {
+    &self.name
+  }
+
+  fn url(&self) -> &str {
+    &self.url
+  }
+
+  fn image_url(&self) -> Option<&str> {
+    self.image_url.as_deref()
+  }
+}
+
+impl WebLink for DiffWebLinkInfo {
+  fn name(&self) -> &str {
+    &self.name
+  }
+
+  fn url(&self) -> &str {
+    &self.url
+  }
+
+  fn image_url(&self) -> Option<&str> {
+    Some(&self.image_url)
+  }
+}
+
 /// The WorkInProgressInput entity contains additional information for a change set to WorkInProgress/ReadyForReview.
 #[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -2326,15 +3427,105 @@ pub struct QueryParams {
   pub start: Option<u32>,
 }
 
+impl QueryParams {
+  /// Get the URL of the query changes request made with these parameters.
+  pub(crate) fn url(&self) -> Result<String> {
+    crate::handler::url_with_query("a/changes/", self)
+  }
+
+  /// Parse the response of a query changes request made with these parameters.
+  ///
+  /// Gerrit responds with a list of changes for one query, and with a list of lists for more queries.
+  /// The result always has one inner list per query, so it is empty without any query.
+  pub(crate) fn parse_changes(&self, json: &str) -> Result<Vec<Vec<ChangeInfo>>> {
+    let num_queries = self.search_queries.as_ref().map_or(0, Vec::len);
+    match num_queries {
+      0 => return Ok(Vec::new()),
+      1 => return Ok(vec![serde_json::from_str(json)?]),
+      _ => {}
+    }
+    let changes: Vec<Vec<ChangeInfo>> = serde_json::from_str(json)?;
+    if changes.len() != num_queries {
+      return Err(crate::error::Error::MissingResponseField(format!(
+        "results of {} queries, got {}",
+        num_queries,
+        changes.len()
+      )));
+    }
+    Ok(changes)
+  }
+}
+
+/// Get the URL of the get change request, with the given additional options.
+pub(crate) fn change_url(change_id: &str, additional_opts: Option<Vec<AdditionalOpt>>) -> Result<String> {
+  let query = QueryParams {
+    additional_opts,
+    ..Default::default()
+  };
+  crate::handler::url_with_query(&format!("a/changes/{}/", change_id), &query)
+}
+
+/// Get the URL of the set review request of a revision.
+pub(crate) fn review_url(change_id: &str, revision_id: &str) -> String {
+  format!("a/changes/{}/revisions/{}/review", change_id, revision_id)
+}
+
+/// Iterator over the changes of a query, fetching the pages on demand.
+///
+/// Created by [ChangeEndpoints::query_changes_paged](trait.ChangeEndpoints.html#tymethod.query_changes_paged).
+pub struct ChangeQueryIter<'a, E: ChangeEndpoints> {
+  endpoints: &'a mut E,
+  query: QueryParams,
+  page: std::vec::IntoIter<ChangeInfo>,
+  /// Start of the next page to fetch, if any.
+  next_start: Option<u32>,
+}
+
+impl<'a, E: ChangeEndpoints> ChangeQueryIter<'a, E> {
+  /// Create an iterator over the changes of the query, starting at its start parameter.
+  pub fn new(endpoints: &'a mut E, query: &QueryParams) -> Self {
+    Self {
+      endpoints,
+      query: query.clone(),
+      page: Vec::new().into_iter(),
+      next_start: Some(query.start.unwrap_or(0)),
+    }
+  }
+}
+
+impl<E: ChangeEndpoints> Iterator for ChangeQueryIter<'_, E> {
+  type Item = Result<ChangeInfo>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if let Some(change) = self.page.next() {
+      return Some(Ok(change));
+    }
+    let start = self.next_start.take()?;
+    self.query.start = Some(start);
+    let changes = match self.endpoints.query_changes_single(&self.query) {
+      Ok(changes) => changes,
+      Err(e) => return Some(Err(e)),
+    };
+    if changes.last().map_or(false, |change| change.more_changes) {
+      self.next_start = Some(start + changes.len() as u32);
+    }
+    self.page = changes.into_iter();
+    self.page.next().map(Ok)
+  }
+}
+
 /// Patch query parameters available for the get_patch endpoint.
 #[skip_serializing_none]
 #[derive(Debug, Clone, Default, Serialize)]
 pub struct PatchParams {
-  /// Returns the patch as a single file inside of a ZIP archive.
+  /// Returns the patch as a single file inside of a ZIP archive, given as a bare `zip` flag.
   /// Clients can expand the ZIP to obtain the plain text patch, avoiding the need for a base64 decoding step.
   /// This option implies `download`.
+  #[serde(serialize_with = "crate::details::serde_flag::serialize")]
   pub zip: Option<()>,
-  /// Suggest the browser save the patch as `commitsha1.diff.base64`
+  /// Suggest the browser save the patch as `commitsha1.diff.base64`, given as a bare `download` flag.
+  /// The content is base64 encoded as without this option.
+  #[serde(serialize_with = "crate::details::serde_flag::serialize")]
   pub download: Option<()>,
   /// If the `path` parameter is set, the returned content is a diff of the single file that the path refers to.
   pub path: Option<String>,
@@ -2353,6 +3544,7 @@ pub enum CompressFormat {
 #[derive(Debug, Clone, Default, Serialize)]
 pub struct DiffParams {
   /// If the intraline parameter is specified, intraline differences are included in the diff.
+  #[serde(serialize_with = "crate::details::serde_flag::serialize")]
   pub intraline: Option<()>,
   /// The base parameter can be specified to control the base patch set from which the diff should be generated.
   pub base: Option<u32>,
@@ -2383,11 +3575,12 @@ pub enum DiffWhitespace {
 pub struct ListFilesParams {
   /// The request parameter reviewed changes the response to return a list of the paths the caller has marked as reviewed.
   /// Clients that also need the FileInfo should make two requests.
+  #[serde(serialize_with = "crate::details::serde_flag::serialize")]
   pub reviewed: Option<()>,
   /// The request parameter q changes the response to return a list of all files (modified or unmodified) that
   /// contain that substring in the path name. This is useful to implement suggestion services finding a file by partial name.
   /// Clients that also need the FileInfo should make two requests.
-  pub q: Option<()>,
+  pub q: Option<String>,
   /// The request parameter base changes the response to return a map of the files which are different in this
   /// commit compared to the given revision. The revision must correspond to a patch set in the change.
   pub base: Option<u32>,
@@ -2398,6 +3591,25 @@ pub struct ListFilesParams {
   pub parent: Option<u32>,
 }
 
+impl ListFilesParams {
+  /// Check that at most one of the mutually exclusive options is set, and get whether the
+  /// response is a list of paths, i.e. whether reviewed or q is set.
+  pub(crate) fn returns_paths(&self) -> Result<bool> {
+    let set = [
+      self.reviewed.is_some(),
+      self.q.is_some(),
+      self.base.is_some(),
+      self.parent.is_some(),
+    ];
+    if set.iter().filter(|set| **set).count() > 1 {
+      return Err(crate::error::Error::InvalidInput(
+        "the reviewed, q, parent, and base options are mutually exclusive".into(),
+      ));
+    }
+    Ok(self.reviewed.is_some() || self.q.is_some())
+  }
+}
+
 /// GetContent query parameters available for the get_content endpoint.
 #[skip_serializing_none]
 #[derive(Debug, Clone, Default, Serialize)]
@@ -2408,6 +3620,24 @@ pub struct GetContentParams {
   pub parent: Option<i32>,
 }
 
+/// SuggestReviewers query parameters available for the suggest_reviewers endpoint.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SuggestReviewersParams {
+  /// The query string to match accounts and groups against.
+  #[serde(rename = "q")]
+  pub query: String,
+  /// The maximum number of reviewers to suggest.
+  #[serde(rename = "n")]
+  pub limit: Option<u32>,
+  /// Exclude groups from the suggestions, given as a bare `exclude-groups` flag.
+  #[serde(rename = "exclude-groups", serialize_with = "crate::details::serde_flag::serialize")]
+  pub exclude_groups: Option<()>,
+  /// The state the suggested accounts are meant to be added as, e.g. `CC` to suggest CCs.
+  #[serde(rename = "reviewer-state")]
+  pub reviewer_state: Option<ReviewerState>,
+}
+
 /// Additional fields can be obtained by adding `o` parameters, each option requires more database
 /// lookups and slows down the query response time to the client so they are generally disabled by default.
 #[derive(AsRefStr, Display, PartialEq, Eq, Clone, Debug, Serialize)]
@@ -2476,6 +3706,40 @@ pub enum AdditionalOpt {
   TrackingIds,
 }
 
+/// Curated sets of additional options for common use cases.
+///
+/// Each profile includes the prerequisites of the options it contains.
+pub struct Profiles;
+
+impl Profiles {
+  /// Options for listing changes in a dashboard:
+  /// `LABELS`, `DETAILED_ACCOUNTS` and `REVIEWED`.
+  pub fn dashboard() -> Vec<AdditionalOpt> {
+    vec![
+      AdditionalOpt::Labels,
+      AdditionalOpt::DetailedAccounts,
+      AdditionalOpt::Reviewed,
+    ]
+  }
+
+  /// Options for deciding whether a change can be merged, e.g. by CI systems:
+  /// `DETAILED_LABELS`, `CURRENT_REVISION`, `CURRENT_COMMIT` and `SUBMITTABLE`.
+  pub fn gating() -> Vec<AdditionalOpt> {
+    vec![
+      AdditionalOpt::DetailedLabels,
+      AdditionalOpt::CurrentRevision,
+      AdditionalOpt::CurrentCommit,
+      AdditionalOpt::Submittable,
+    ]
+  }
+
+  /// Options for the cheapest possible query, skipping the diff statistics:
+  /// `SKIP_DIFFSTAT`.
+  pub fn minimal() -> Vec<AdditionalOpt> {
+    vec![AdditionalOpt::SkipDiffstat]
+  }
+}
+
 #[derive(Debug, Clone)]
 pub enum QueryStr {
   Raw(String),
@@ -2493,8 +3757,48 @@ pub enum QueryOpr {
 pub enum SearchOpr {
   Is(Is),
   Owner(String),
+  /// Changes owned by the calling user, same as `owner:self`.
+  OwnerSelf,
   Reviewer(String),
+  /// Changes the calling user is a reviewer of, same as `reviewer:self`.
+  ReviewerSelf,
   Limit(u32),
+  /// Changes that were submitted together in the submission with the given id.
+  SubmissionId(String),
+  /// `project:`: changes in the given project.
+  Project(String),
+  /// `branch:`: changes on the given destination branch, with or without the `refs/heads/` prefix.
+  Branch(String),
+  /// `status:`: changes in the given state.
+  Status(Status),
+  /// `topic:`: changes with the given topic.
+  Topic(String),
+  /// `message:`: changes whose commit message contains the given words.
+  Message(String),
+  /// `age:`: changes that have not been updated within the given relative time, e.g. `1d` or `2weeks`.
+  Age(String),
+  /// `label:`: changes with the given vote on the label, rendered like `label:Code-Review+2`.
+  Label(String, i32),
+  /// `file:`: changes that touch a file matching the given name or regular expression (starting with `^`).
+  File(String),
+  /// `path:`: changes that touch a file with exactly the given path, or matching the regular expression
+  /// (starting with `^`).
+  Path(String),
+  /// `after:`: changes modified after the given date or timestamp, e.g. `2020-01-31`.
+  After(String),
+  /// `before:`: changes modified before the given date or timestamp, e.g. `2020-01-31 12:00:00`.
+  Before(String),
+}
+
+/// An account as accepted by the account related search operators.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Account {
+  /// The calling user, rendered as `self`.
+  Myself,
+  /// The account with the given email address.
+  Email(String),
+  /// The account with the given numeric ID.
+  Id(u32),
 }
 
 #[derive(Debug, AsRefStr, Display, PartialEq, Eq, Clone)]
@@ -2513,32 +3817,196 @@ pub enum GroupOpr {
   End,
 }
 
+/// Values of the `is:` search operator.
+///
+/// Each variant renders as the exact token documented by Gerrit, e.g. `Is::Wip` as `wip`.
 #[derive(Debug, AsRefStr, Display, PartialEq, Eq, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 #[strum(serialize_all = "snake_case")]
 pub enum Is {
+  /// `is:assigned`: the change has an assignee.
   Assigned,
+  /// `is:unassigned`: the change has no assignee.
   Unassigned,
+  /// `is:starred`: the change has been starred by the current user with the default label.
   Starred,
+  /// `is:watched`: the change matches one of the current user’s watch filters.
   Watched,
+  /// `is:reviewed`: the change has at least one non-zero score on any label.
   Reviewed,
+  /// `is:owner`: the current user is the change owner. Same as `owner:self`.
   Owner,
+  /// `is:reviewer`: the current user is a reviewer of the change. Same as `reviewer:self`.
   Reviewer,
+  /// `is:cc`: the current user is in CC of the change.
   Cc,
+  /// `is:ignored`: the change is ignored by the current user. Same as `star:ignore`.
   Ignored,
+  /// `is:muted`: the change is muted by the current user, i.e. hidden until it gets a new patch set.
+  Muted,
+  /// `is:attention`: the current user is in the attention set of the change.
+  Attention,
+  /// `is:new`: the change is open and has not been merged or abandoned.
   New,
+  /// `is:open`: the change is open. Same as `is:pending` and `status:open`.
   Open,
+  /// `is:pending`: the change is open. Same as `is:open` and `status:open`.
   Pending,
+  /// `is:draft`: the change is a draft. Draft changes are no longer supported by recent Gerrit versions,
+  /// prefer `is:wip` or `is:private` there.
   Draft,
+  /// `is:closed`: the change is either merged or abandoned. Same as `status:closed`.
   Closed,
+  /// `is:merged`: the change has been merged.
   Merged,
+  /// `is:abandoned`: the change has been abandoned.
   Abandoned,
+  /// `is:submittable`: the change is submittable according to the submit rules.
   Submittable,
+  /// `is:mergeable`: the change has no merge conflicts.
   Mergeable,
+  /// `is:private`: the change is private.
   Private,
+  /// `is:wip`: the change is work in progress.
   Wip,
 }
 
+/// Values of the `status:` search operator.
+#[derive(Debug, AsRefStr, Display, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Status {
+  /// `status:open`: the change is open. Same as `status:pending` and `status:new`.
+  Open,
+  /// `status:pending`: the change is open. Same as `status:open` and `status:new`.
+  Pending,
+  /// `status:new`: the change is open. Same as `status:open` and `status:pending`.
+  New,
+  /// `status:reviewed`: the change is open and has at least one non-zero score on any label.
+  Reviewed,
+  /// `status:closed`: the change is either merged or abandoned.
+  Closed,
+  /// `status:merged`: the change has been merged.
+  Merged,
+  /// `status:abandoned`: the change has been abandoned.
+  Abandoned,
+  /// `status:submitted`: the change has been submitted but is not yet merged. Only on old Gerrit versions.
+  Submitted,
+  /// `status:draft`: the change is a draft. Only on old Gerrit versions.
+  Draft,
+}
+
+impl From<ChangeStatus> for Status {
+  fn from(status: ChangeStatus) -> Self {
+    match status {
+      ChangeStatus::New => Status::New,
+      ChangeStatus::Merged => Status::Merged,
+      ChangeStatus::Submitted => Status::Submitted,
+      ChangeStatus::Abandoned => Status::Abandoned,
+      ChangeStatus::Draft => Status::Draft,
+    }
+  }
+}
+
+/// Builder of a change search query.
+///
+/// Operators are juxtaposed in the order they are added, which Gerrit treats as an implicit `AND`.
+/// The query is validated when it is serialized, see `QueryOpr::validate`.
+/// E.g. `ChangeQueryBuilder::new().owner("self").and().status(Status::Open)` renders as `owner:self AND status:open`.
+#[derive(Debug, Clone, Default)]
+pub struct ChangeQueryBuilder {
+  operators: Vec<QueryOpr>,
+}
+
+impl ChangeQueryBuilder {
+  /// Start an empty query.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Add any search operator.
+  pub fn search(mut self, opr: SearchOpr) -> Self {
+    self.operators.push(QueryOpr::Search(opr));
+    self
+  }
+
+  /// Changes owned by the given account, e.g. `self`.
+  pub fn owner(self, account: &str) -> Self {
+    self.search(SearchOpr::Owner(account.into()))
+  }
+
+  /// Changes with the given account as reviewer, e.g. `self`.
+  pub fn reviewer(self, account: &str) -> Self {
+    self.search(SearchOpr::Reviewer(account.into()))
+  }
+
+  /// Changes matching the given `is:` operator.
+  pub fn is(self, is: Is) -> Self {
+    self.search(SearchOpr::Is(is))
+  }
+
+  /// Changes in the given project.
+  pub fn project(self, project: &str) -> Self {
+    self.search(SearchOpr::Project(project.into()))
+  }
+
+  /// Changes on the given destination branch.
+  pub fn branch(self, branch: &str) -> Self {
+    self.search(SearchOpr::Branch(branch.into()))
+  }
+
+  /// Changes in the given state.
+  pub fn status<S: Into<Status>>(self, status: S) -> Self {
+    self.search(SearchOpr::Status(status.into()))
+  }
+
+  /// Changes with the given topic.
+  pub fn topic(self, topic: &str) -> Self {
+    self.search(SearchOpr::Topic(topic.into()))
+  }
+
+  /// Changes whose commit message contains the given words.
+  pub fn message(self, message: &str) -> Self {
+    self.search(SearchOpr::Message(message.into()))
+  }
+
+  /// Add an explicit `AND` between the previous and the next operator.
+  pub fn and(mut self) -> Self {
+    self.operators.push(QueryOpr::Bool(BoolOpr::And));
+    self
+  }
+
+  /// Add an `OR` between the previous and the next operator.
+  pub fn or(mut self) -> Self {
+    self.operators.push(QueryOpr::Bool(BoolOpr::Or));
+    self
+  }
+
+  /// Negate the next operator or group.
+  #[allow(clippy::should_implement_trait)]
+  pub fn not(mut self) -> Self {
+    self.operators.push(QueryOpr::Bool(BoolOpr::Not));
+    self
+  }
+
+  /// Add a parenthesized group of operators built by `f`.
+  pub fn group<F>(mut self, f: F) -> Self
+  where
+    F: FnOnce(Self) -> Self,
+  {
+    self.operators.push(QueryOpr::Group(GroupOpr::Begin));
+    let mut inner = f(Self::new()).operators;
+    self.operators.append(&mut inner);
+    self.operators.push(QueryOpr::Group(GroupOpr::End));
+    self
+  }
+
+  /// Return the assembled query.
+  pub fn build(self) -> QueryStr {
+    QueryStr::Cooked(self.operators)
+  }
+}
+
 impl serde::Serialize for QueryStr {
   fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
   where
@@ -2547,12 +4015,11 @@ impl serde::Serialize for QueryStr {
     match self {
       QueryStr::Raw(s) => serializer.serialize_str(s.as_str()),
       QueryStr::Cooked(operators) => {
-        let mut strings: Vec<String> = Vec::new();
-        strings.reserve(operators.len());
+        QueryOpr::validate(operators).map_err(serde::ser::Error::custom)?;
+        let mut strings: Vec<String> = Vec::with_capacity(operators.len());
         for opr in operators {
           strings.push(format!("{}", opr));
         }
-        println!("{:#?}", strings);
         let joined = strings.join(" ");
         serializer.serialize_str(joined.as_str())
       }
@@ -2560,6 +4027,49 @@ impl serde::Serialize for QueryStr {
   }
 }
 
+impl QueryOpr {
+  /// Check that a sequence of operators forms a well-formed query.
+  ///
+  /// Groups must be balanced and non-empty, and boolean operators must be followed by an operand.
+  /// `AND` and `OR` must also be preceded by an operand.
+  pub fn validate(operators: &[QueryOpr]) -> std::result::Result<(), String> {
+    let mut depth: usize = 0;
+    let mut expect_operand = true;
+    for opr in operators {
+      match opr {
+        QueryOpr::Search(_) => expect_operand = false,
+        QueryOpr::Group(GroupOpr::Begin) => {
+          depth += 1;
+          expect_operand = true;
+        }
+        QueryOpr::Group(GroupOpr::End) => {
+          if expect_operand {
+            return Err(format!("missing operand before '{}'", GroupOpr::End));
+          }
+          if depth == 0 {
+            return Err(format!("unbalanced '{}' in query", GroupOpr::End));
+          }
+          depth -= 1;
+        }
+        QueryOpr::Bool(BoolOpr::Not) => expect_operand = true,
+        QueryOpr::Bool(b) => {
+          if expect_operand {
+            return Err(format!("missing operand before '{}'", b));
+          }
+          expect_operand = true;
+        }
+      }
+    }
+    if expect_operand && !operators.is_empty() {
+      return Err("dangling operator at the end of query".into());
+    }
+    if depth != 0 {
+      return Err(format!("unbalanced '{}' in query", GroupOpr::Begin));
+    }
+    Ok(())
+  }
+}
+
 impl Display for QueryOpr {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), Error> {
     match self {
@@ -2575,8 +4085,616 @@ impl Display for SearchOpr {
     match self {
       SearchOpr::Is(o) => write!(f, "is:{}", o),
       SearchOpr::Owner(o) => write!(f, "owner:{}", o),
+      SearchOpr::OwnerSelf => write!(f, "owner:{}", Account::Myself),
       SearchOpr::Reviewer(o) => write!(f, "reviewer:{}", o),
+      SearchOpr::ReviewerSelf => write!(f, "reviewer:{}", Account::Myself),
       SearchOpr::Limit(o) => write!(f, "limit:{}", o),
+      SearchOpr::SubmissionId(o) => write!(f, "submissionid:{}", o),
+      SearchOpr::Project(o) => write!(f, "project:{}", o),
+      SearchOpr::Branch(o) => write!(f, "branch:{}", o),
+      SearchOpr::Status(o) => write!(f, "status:{}", o),
+      SearchOpr::Topic(o) => write!(f, "topic:{}", Quoted(o)),
+      SearchOpr::Message(o) => write!(f, "message:{}", Quoted(o)),
+      SearchOpr::Age(o) => write!(f, "age:{}", o),
+      SearchOpr::Label(name, 0) => write!(f, "label:{}=0", name),
+      SearchOpr::Label(name, value) => write!(f, "label:{}{:+}", name, value),
+      SearchOpr::File(o) => write!(f, "file:{}", Quoted(o)),
+      SearchOpr::Path(o) => write!(f, "path:{}", Quoted(o)),
+      SearchOpr::After(o) => write!(f, "after:{}", Quoted(o)),
+      SearchOpr::Before(o) => write!(f, "before:{}", Quoted(o)),
+    }
+  }
+}
+
+/// Operator value that is wrapped in double quotes if it contains whitespace, so it is taken as a single term.
+struct Quoted<'a>(&'a str);
+
+impl Display for Quoted<'_> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), Error> {
+    if self.0.contains(char::is_whitespace) {
+      write!(f, "\"{}\"", self.0)
+    } else {
+      f.write_str(self.0)
+    }
+  }
+}
+
+impl Display for Account {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), Error> {
+    match self {
+      Account::Myself => f.write_str("self"),
+      Account::Email(email) => f.write_str(email),
+      Account::Id(id) => write!(f, "{}", id),
+    }
+  }
+}
+
+impl From<Account> for String {
+  fn from(account: Account) -> Self {
+    account.to_string()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::error::Error;
+
+  #[test]
+  fn patch_set_number_round_trip() {
+    let number: PatchSetNumber = serde_json::from_str("3").unwrap();
+    assert_eq!(number, PatchSetNumber::Number(3));
+    assert_eq!(serde_json::to_string(&number).unwrap(), "3");
+    let edit: PatchSetNumber = serde_json::from_str(r#""edit""#).unwrap();
+    assert_eq!(edit, PatchSetNumber::Edit);
+    assert_eq!(serde_json::to_string(&edit).unwrap(), r#""edit""#);
+    assert!(serde_json::from_str::<PatchSetNumber>(r#""draft""#).is_err());
+    assert!(serde_json::from_str::<PatchSetNumber>("-1").is_err());
+  }
+
+  #[test]
+  fn revision_of_change_edit() {
+    let revision: RevisionInfo = serde_json::from_str(r#"{ "kind": "REWORK", "_number": "edit" }"#).unwrap();
+    assert_eq!(revision._number, PatchSetNumber::Edit);
+    assert_eq!(revision._number.to_string(), "edit");
+  }
+
+  #[test]
+  fn diff_content_with_intraline_edits() {
+    let content: DiffContent = serde_json::from_str(
+      r#"{
+        "a": ["  int x = foo(a, b);"],
+        "b": ["  long x = bar(a, b, c);"],
+        "edit_a": [[2, 3], [6, 3], [5, 0]],
+        "edit_b": [[2, 4], [6, 3], [5, 3]]
+      }"#,
+    )
+    .unwrap();
+    let edit_a = content.edit_a.unwrap();
+    assert_eq!(edit_a.edits, vec![(2, 3), (6, 3), (5, 0)]);
+    assert_eq!(content.edit_b.unwrap().edits, vec![(2, 4), (6, 3), (5, 3)]);
+    assert_eq!(serde_json::to_string(&edit_a).unwrap(), "[[2,3],[6,3],[5,0]]");
+  }
+
+  #[test]
+  fn self_search_operators() {
+    assert_eq!(SearchOpr::OwnerSelf.to_string(), "owner:self");
+    assert_eq!(SearchOpr::ReviewerSelf.to_string(), "reviewer:self");
+    let query = QueryStr::Cooked(vec![
+      QueryOpr::Search(SearchOpr::OwnerSelf),
+      QueryOpr::Bool(BoolOpr::Or),
+      QueryOpr::Search(SearchOpr::ReviewerSelf),
+    ]);
+    assert_eq!(
+      serde_json::to_string(&query).unwrap(),
+      r#""owner:self OR reviewer:self""#
+    );
+  }
+
+  #[test]
+  fn account_search_operators() {
+    let email = SearchOpr::Owner(Account::Email("jdoe@example.com".into()).into());
+    assert_eq!(email.to_string(), "owner:jdoe@example.com");
+    let id = SearchOpr::Reviewer(Account::Id(1000096).into());
+    assert_eq!(id.to_string(), "reviewer:1000096");
+    assert_eq!(String::from(Account::Myself), "self");
+  }
+
+  fn search(opr: SearchOpr) -> QueryOpr {
+    QueryOpr::Search(opr)
+  }
+
+  #[test]
+  fn balanced_query_serializes() {
+    let query = QueryStr::Cooked(vec![
+      search(SearchOpr::Is(Is::Open)),
+      QueryOpr::Group(GroupOpr::Begin),
+      search(SearchOpr::Project("gerlib".into())),
+      QueryOpr::Bool(BoolOpr::Or),
+      QueryOpr::Bool(BoolOpr::Not),
+      search(SearchOpr::Topic("cleanup".into())),
+      QueryOpr::Group(GroupOpr::End),
+    ]);
+    assert_eq!(
+      serde_json::to_string(&query).unwrap(),
+      r#""is:open ( project:gerlib OR NOT topic:cleanup )""#
+    );
+  }
+
+  #[test]
+  fn unbalanced_query_is_rejected() {
+    let query = QueryStr::Cooked(vec![QueryOpr::Group(GroupOpr::Begin), search(SearchOpr::Is(Is::Open))]);
+    let error = serde_json::to_string(&query).unwrap_err();
+    assert_eq!(error.to_string(), "unbalanced '(' in query");
+    let query = QueryStr::Cooked(vec![search(SearchOpr::Is(Is::Open)), QueryOpr::Group(GroupOpr::End)]);
+    assert_eq!(
+      serde_json::to_string(&query).unwrap_err().to_string(),
+      "unbalanced ')' in query"
+    );
+  }
+
+  #[test]
+  fn trailing_and_is_rejected() {
+    let query = QueryStr::Cooked(vec![search(SearchOpr::Is(Is::Open)), QueryOpr::Bool(BoolOpr::And)]);
+    let error = serde_json::to_string(&query).unwrap_err();
+    assert_eq!(error.to_string(), "dangling operator at the end of query");
+    let query = QueryStr::Cooked(vec![QueryOpr::Bool(BoolOpr::And), search(SearchOpr::Is(Is::Open))]);
+    assert!(serde_json::to_string(&query).is_err());
+  }
+
+  fn replacement(path: &str, start_line: u32, end_line: u32) -> FixReplacementInfo {
+    FixReplacementInfo {
+      path: path.into(),
+      range: CommentRange {
+        start_line,
+        start_character: 0,
+        end_line,
+        end_character: 0,
+      },
+      replacement: String::new(),
+    }
+  }
+
+  fn fix(replacements: Vec<FixReplacementInfo>) -> FixSuggestionInfo {
+    FixSuggestionInfo {
+      fix_id: None,
+      description: "Remove dead code".into(),
+      replacements,
+    }
+  }
+
+  #[test]
+  fn disjoint_replacements_are_valid() {
+    let adjacent = fix(vec![replacement("lib.rs", 1, 3), replacement("lib.rs", 3, 5)]);
+    assert!(adjacent.validate().is_ok());
+    let other_files = fix(vec![replacement("lib.rs", 1, 3), replacement("main.rs", 1, 3)]);
+    assert!(other_files.validate().is_ok());
+  }
+
+  #[test]
+  fn overlapping_replacements_are_rejected() {
+    let overlapping = fix(vec![replacement("lib.rs", 1, 4), replacement("lib.rs", 3, 5)]);
+    let error = overlapping.validate().unwrap_err();
+    assert!(matches!(error, Error::WrongQuery(_)));
+    assert_eq!(
+      error.verbose(1).to_string(),
+      "Failed to generate query: replacement ranges in 'lib.rs' overlap"
+    );
+    assert!(matches!(
+      fix(vec![replacement("lib.rs", 5, 3)]).validate(),
+      Err(Error::WrongQuery(_))
+    ));
+    assert!(matches!(fix(vec![]).validate(), Err(Error::WrongQuery(_))));
+  }
+
+  #[test]
+  fn review_validates_fix_suggestions() {
+    let mut review = ReviewInputBuilder::new().message("Lint findings").build();
+    RobotCommentBuilder::new("lib.rs", "clippy", "run-1", "dead code")
+      .line(2)
+      .fix("Remove dead code", vec![replacement("lib.rs", 1, 3)])
+      .attach_to(&mut review)
+      .unwrap();
+    assert!(review.validate().is_ok());
+    let robot_comment = &mut review.robot_comments.as_mut().unwrap().get_mut("lib.rs").unwrap()[0];
+    robot_comment.fix_suggestions.as_mut().unwrap()[0]
+      .replacements
+      .push(replacement("lib.rs", 2, 4));
+    assert!(matches!(review.validate(), Err(Error::WrongQuery(_))));
+  }
+
+  #[test]
+  fn on_behalf_of_requires_keep_drafts() {
+    let review = |drafts| ReviewInput {
+      on_behalf_of: Some("1000096".into()),
+      drafts,
+      ..ReviewInputBuilder::new().label("Code-Review", 1).build()
+    };
+    for drafts in [DraftHandling::Publish, DraftHandling::PublishAllRevisions] {
+      let error = review(Some(drafts.clone())).validate().unwrap_err();
+      assert!(matches!(error, Error::WrongQuery(_)), "{:?}", drafts);
+      assert_eq!(
+        error.verbose(1).to_string(),
+        "Failed to generate query: only KEEP drafts handling is allowed together with on_behalf_of"
+      );
     }
+    assert!(review(Some(DraftHandling::Keep)).validate().is_ok());
+    assert!(review(None).validate().is_ok());
+    let own_review = ReviewInput {
+      on_behalf_of: None,
+      ..review(Some(DraftHandling::Publish))
+    };
+    assert!(own_review.validate().is_ok());
+  }
+
+  #[test]
+  fn ready_and_work_in_progress_are_exclusive() {
+    let review = |ready, work_in_progress| ReviewInput {
+      ready,
+      work_in_progress,
+      ..ReviewInputBuilder::new().message("Done").build()
+    };
+    let error = review(Some(true), Some(true)).validate().unwrap_err();
+    assert!(matches!(error, Error::WrongQuery(_)));
+    assert_eq!(
+      error.verbose(1).to_string(),
+      "Failed to generate query: ready and work_in_progress cannot both be set"
+    );
+    assert!(review(Some(true), None).validate().is_ok());
+    assert!(review(None, Some(true)).validate().is_ok());
+    assert!(review(Some(true), Some(false)).validate().is_ok());
+  }
+
+  fn hashtags(add: &[&str], remove: &[&str]) -> HashtagsInput {
+    let list = |tags: &[&str]| Some(tags.iter().map(|tag| tag.to_string()).collect());
+    HashtagsInput {
+      add: list(add),
+      remove: list(remove),
+    }
+  }
+
+  #[test]
+  fn hashtags_are_trimmed_and_deduplicated() {
+    let input = hashtags(&[" feature ", "bugfix", "feature", "\trelease\n"], &[" stale "]);
+    let normalized = input.normalized().unwrap();
+    assert_eq!(normalized.add.unwrap(), vec!["feature", "bugfix", "release"]);
+    assert_eq!(normalized.remove.unwrap(), vec!["stale"]);
+    let normalized = HashtagsInput {
+      add: None,
+      remove: None,
+    }
+    .normalized()
+    .unwrap();
+    assert!(normalized.add.is_none() && normalized.remove.is_none());
+  }
+
+  #[test]
+  fn hashtags_with_comma_are_rejected() {
+    let error = hashtags(&["feature,bugfix"], &[]).normalized().unwrap_err();
+    assert!(matches!(error, Error::WrongQuery(_)));
+    assert_eq!(
+      error.verbose(1).to_string(),
+      "Failed to generate query: invalid hashtag 'feature,bugfix': hashtags must be non-empty and cannot contain commas"
+    );
+    assert!(matches!(
+      hashtags(&[], &["a,b"]).normalized(),
+      Err(Error::WrongQuery(_))
+    ));
+    assert!(matches!(hashtags(&["  "], &[]).normalized(), Err(Error::WrongQuery(_))));
+  }
+
+  #[test]
+  fn revision_fetch_commands() {
+    let mut revision: RevisionInfo = serde_json::from_str(
+      r#"{
+        "_number": 2,
+        "ref": "refs/changes/65/3965/2",
+        "fetch": {
+          "git": { "url": "git://localhost/gerrit", "ref": "refs/changes/65/3965/2" },
+          "http": {
+            "url": "http://myuser@localhost:8080/gerrit",
+            "ref": "refs/changes/65/3965/2",
+            "commands": {
+              "Checkout": "git fetch http://myuser@localhost:8080/gerrit refs/changes/65/3965/2 && git checkout FETCH_HEAD"
+            }
+          }
+        }
+      }"#,
+    )
+    .unwrap();
+    assert_eq!(
+      revision.fetch_command("http", "Checkout").as_deref(),
+      Some("git fetch http://myuser@localhost:8080/gerrit refs/changes/65/3965/2 && git checkout FETCH_HEAD")
+    );
+    assert_eq!(revision.fetch_command("http", "Pull"), None);
+    assert_eq!(revision.fetch_command("git", "Checkout"), None);
+    assert_eq!(revision.fetch_command("ssh", "Checkout"), None);
+    let (protocol, fetch) = revision.preferred_fetch().unwrap();
+    assert_eq!(protocol, "http");
+    assert_eq!(fetch.url, "http://myuser@localhost:8080/gerrit");
+    let ssh: FetchInfo =
+      serde_json::from_str(r#"{ "url": "ssh://myuser@localhost:29418/gerrit", "ref": "refs/changes/65/3965/2" }"#)
+        .unwrap();
+    revision.fetch.insert("ssh".into(), ssh);
+    assert_eq!(revision.preferred_fetch().unwrap().0, "ssh");
+    revision.fetch.clear();
+    assert!(revision.preferred_fetch().is_none());
+  }
+
+  #[test]
+  fn reviewer_history_from_updates() {
+    let update = |updated: &str, reviewer: u32, state: &str| {
+      serde_json::json!({
+        "updated": updated,
+        "updated_by": { "_account_id": 1000096 },
+        "reviewer": { "_account_id": reviewer },
+        "state": state,
+      })
+    };
+    let updates: Vec<ReviewerUpdateInfo> = serde_json::from_value(serde_json::json!([
+      update("2013-02-21 11:20:00.000000000", 1000097, "REMOVED"),
+      update("2013-02-21 11:16:36.775000000", 1000097, "CC"),
+      update("2013-02-21 11:17:00.000000000", 1000098, "REVIEWER"),
+      update("2013-02-21 11:18:00.000000000", 1000097, "REVIEWER"),
+    ]))
+    .unwrap();
+    let histories = ReviewerHistory::from_updates(&updates);
+    assert_eq!(histories.len(), 2);
+    assert_eq!(histories[0].reviewer.account_id, 1000097);
+    let states: Vec<&ReviewerState> = histories[0].changes.iter().map(|change| &change.state).collect();
+    assert_eq!(
+      states,
+      vec![&ReviewerState::Cc, &ReviewerState::Reviewer, &ReviewerState::Removed]
+    );
+    assert_eq!(histories[0].current_state(), Some(&ReviewerState::Removed));
+    assert_eq!(histories[1].reviewer.account_id, 1000098);
+    assert_eq!(histories[1].changes.len(), 1);
+    assert_eq!(histories[1].current_state(), Some(&ReviewerState::Reviewer));
+    assert!(ReviewerHistory::from_updates(&[]).is_empty());
+  }
+
+  #[test]
+  fn profiles_render_as_query_options() {
+    let url = |additional_opts: Vec<AdditionalOpt>| {
+      QueryParams {
+        search_queries: Some(vec![QueryStr::Raw("is:open".into())]),
+        additional_opts: Some(additional_opts),
+        ..Default::default()
+      }
+      .url()
+      .unwrap()
+    };
+    assert_eq!(
+      url(Profiles::dashboard()),
+      "a/changes/?q=is%3Aopen&o=LABELS&o=DETAILED_ACCOUNTS&o=REVIEWED"
+    );
+    assert_eq!(
+      url(Profiles::gating()),
+      "a/changes/?q=is%3Aopen&o=DETAILED_LABELS&o=CURRENT_REVISION&o=CURRENT_COMMIT&o=SUBMITTABLE"
+    );
+    assert_eq!(url(Profiles::minimal()), "a/changes/?q=is%3Aopen&o=SKIP_DIFFSTAT");
+  }
+
+  #[test]
+  fn filter_changes_by_label_vote() {
+    let change = |number: u32, votes: &[i32]| {
+      let mut change: serde_json::Value = serde_json::from_str(crate::http::mock::CHANGE).unwrap();
+      change["_number"] = number.into();
+      if !votes.is_empty() {
+        let all: Vec<serde_json::Value> = votes
+          .iter()
+          .map(|value| serde_json::json!({ "_account_id": 1000096, "value": value }))
+          .collect();
+        let mut label = serde_json::json!({ "all": all });
+        if votes.iter().any(|&value| value < 0) {
+          label["disliked"] = serde_json::json!({ "_account_id": 1000096 });
+        }
+        change["labels"] = serde_json::json!({ "Code-Review": label });
+      }
+      serde_json::from_value::<ChangeInfo>(change).unwrap()
+    };
+    let changes = vec![
+      change(1, &[2, 1]),
+      change(2, &[1]),
+      change(3, &[1, -1]),
+      change(4, &[]),
+      change(5, &[0]),
+    ];
+    let numbers = |changes: Vec<ChangeInfo>| changes.iter().map(|change| change.number).collect::<Vec<_>>();
+    assert_eq!(numbers(filter_by_label(changes.clone(), "Code-Review", 1)), vec![1, 2]);
+    assert_eq!(numbers(filter_by_label(changes.clone(), "Code-Review", 2)), vec![1]);
+    assert_eq!(
+      numbers(filter_by_label(changes.clone(), "Code-Review", -1)),
+      vec![1, 2, 3, 5]
+    );
+    assert!(filter_by_label(changes, "Verified", -2).is_empty());
+  }
+
+  #[test]
+  fn ignored_and_muted_search_operators() {
+    assert_eq!(SearchOpr::Is(Is::Ignored).to_string(), "is:ignored");
+    assert_eq!(SearchOpr::Is(Is::Muted).to_string(), "is:muted");
+    let query = QueryStr::Cooked(vec![
+      QueryOpr::Bool(BoolOpr::Not),
+      search(SearchOpr::Is(Is::Ignored)),
+      QueryOpr::Bool(BoolOpr::And),
+      QueryOpr::Bool(BoolOpr::Not),
+      search(SearchOpr::Is(Is::Muted)),
+    ]);
+    assert_eq!(
+      serde_json::to_string(&query).unwrap(),
+      r#""NOT is:ignored AND NOT is:muted""#
+    );
+    assert_eq!(serde_json::from_str::<Is>(r#""muted""#).unwrap(), Is::Muted);
+  }
+
+  #[test]
+  fn review_result_with_reviewer_error() {
+    let result: ReviewResult = serde_json::from_str(
+      r#"{
+        "labels": null,
+        "reviewers": {
+          "jane.roe@example.com": {
+            "input": "jane.roe@example.com",
+            "reviewers": [{ "_account_id": 1000097, "approvals": {} }]
+          },
+          "Project Owners": {
+            "input": "Project Owners",
+            "error": "The group Project Owners has 20 members. Do you want to add them all as reviewers?",
+            "confirm": true
+          },
+          "nobody@example.com": {
+            "input": "nobody@example.com",
+            "error": "nobody@example.com does not identify a registered user or group"
+          }
+        },
+        "error": "error adding reviewer"
+      }"#,
+    )
+    .unwrap();
+    assert!(result.labels.is_none());
+    assert!(!result.ready);
+    assert_eq!(result.error.as_deref(), Some("error adding reviewer"));
+    let reviewers = result.reviewers.as_ref().unwrap();
+    assert!(reviewers["Project Owners"].confirm);
+    assert!(!reviewers["nobody@example.com"].confirm);
+    assert_eq!(
+      result.failed_reviewers(),
+      vec![
+        (
+          "Project Owners",
+          "The group Project Owners has 20 members. Do you want to add them all as reviewers?"
+        ),
+        (
+          "nobody@example.com",
+          "nobody@example.com does not identify a registered user or group"
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn change_query_builder() {
+    let query = ChangeQueryBuilder::new()
+      .owner("self")
+      .and()
+      .status(Status::Open)
+      .build();
+    assert_eq!(
+      serde_json::to_string(&query).unwrap(),
+      r#""owner:self AND status:open""#
+    );
+    let query = ChangeQueryBuilder::new()
+      .project("gerlib")
+      .not()
+      .is(Is::Wip)
+      .group(|q| q.reviewer("self").or().topic("cleanup"))
+      .build();
+    assert_eq!(
+      serde_json::to_string(&query).unwrap(),
+      r#""project:gerlib NOT is:wip ( reviewer:self OR topic:cleanup )""#
+    );
+    let params = QueryParams {
+      search_queries: Some(vec![ChangeQueryBuilder::new()
+        .owner("self")
+        .and()
+        .status(Status::Open)
+        .build()]),
+      ..Default::default()
+    };
+    assert_eq!(params.url().unwrap(), "a/changes/?q=owner%3Aself+AND+status%3Aopen");
+    let dangling = ChangeQueryBuilder::new().owner("self").and().build();
+    assert!(serde_json::to_string(&dangling).is_err());
+  }
+
+  #[test]
+  fn comment_range_contains_and_overlaps() {
+    let range = |start_line, start_character, end_line, end_character| CommentRange {
+      start_line,
+      start_character,
+      end_line,
+      end_character,
+    };
+    let first = range(2, 4, 5, 10);
+    assert!(first.contains(2, 4));
+    assert!(first.contains(3, 0));
+    assert!(first.contains(5, 9));
+    assert!(!first.contains(2, 3));
+    assert!(!first.contains(5, 10));
+    assert!(!first.contains(6, 0));
+    let adjacent = range(5, 10, 7, 0);
+    assert!(!first.overlaps(&adjacent));
+    assert!(!adjacent.overlaps(&first));
+    let overlapping = range(5, 9, 7, 0);
+    assert!(first.overlaps(&overlapping));
+    assert!(overlapping.overlaps(&first));
+    let inner = range(3, 0, 3, 5);
+    assert!(first.overlaps(&inner));
+    assert!(inner.overlaps(&first));
+    assert!(!first.overlaps(&range(1, 0, 2, 4)));
+  }
+
+  #[test]
+  fn related_changes_chain() {
+    let related = |number: u32, commit: &str, subject: &str| {
+      serde_json::json!({
+        "project": "gerrit",
+        "change_id": format!("I{:040}", number),
+        "commit": { "commit": commit, "parents": [], "subject": subject },
+        "_change_number": number,
+        "_revision_number": 1,
+        "_current_revision_number": 1,
+        "status": "NEW",
+      })
+    };
+    let info: RelatedChangesInfo = serde_json::from_value(serde_json::json!({
+      "changes": [
+        related(3, "c3", "Use the new API"),
+        related(2, "c2", "Add the new API"),
+        related(1, "c1", "Prepare for the new API"),
+      ]
+    }))
+    .unwrap();
+    let numbers = |changes: &[RelatedChangeAndCommitInfo]| {
+      changes
+        .iter()
+        .filter_map(|change| change.change_number)
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(info.position_of(3), Some(0));
+    assert_eq!(info.position_of(2), Some(1));
+    assert_eq!(info.position_of(1), Some(2));
+    assert_eq!(info.position_of(4), None);
+    assert_eq!(numbers(info.ancestors(2)), vec![1]);
+    assert_eq!(numbers(info.descendants(2)), vec![3]);
+    assert_eq!(numbers(info.ancestors(3)), vec![2, 1]);
+    assert!(info.descendants(3).is_empty());
+    assert!(info.ancestors(1).is_empty());
+    assert_eq!(numbers(info.descendants(1)), vec![3, 2]);
+    assert!(info.ancestors(4).is_empty());
+    assert!(info.descendants(4).is_empty());
+  }
+
+  #[test]
+  fn votes_map_conversion() {
+    let votes = Votes::code_review(2).vote("Verified", 1).vote("Code-Review", -1);
+    let map: BTreeMap<String, i32> = votes.into();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["Code-Review"], -1);
+    assert_eq!(map["Verified"], 1);
+    let votes = Votes::from(map);
+    assert_eq!(
+      votes.0,
+      vec![LabelVote::new("Code-Review", -1), LabelVote::new("Verified", 1)]
+    );
+    assert!(BTreeMap::<String, i32>::from(Votes::new()).is_empty());
+    assert!(Votes::from(BTreeMap::new()).0.is_empty());
+    let votes = Votes::verified(1).vote("Code-Review", 2);
+    let json = serde_json::to_string(&votes).unwrap();
+    assert_eq!(json, r#"{"Code-Review":2,"Verified":1}"#);
+    assert_eq!(
+      serde_json::from_str::<Votes>(&json).unwrap(),
+      Votes::code_review(2).vote("Verified", 1)
+    );
   }
 }
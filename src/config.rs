@@ -0,0 +1,167 @@
+//! Config related REST endpoints.
+//!
+//! See [ConfigEndpoints](trait.ConfigEndpoints.html) trait for the REST API.
+
+use crate::Result;
+use serde_derive::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::collections::BTreeMap;
+
+// ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+// REST API
+// ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// This trait describes the server config related REST endpoints.
+pub trait ConfigEndpoints {
+  /// Returns the version of the Gerrit server.
+  fn get_version(&mut self) -> Result<String>;
+
+  /// Returns the information about the Gerrit server configuration.
+  ///
+  /// As result a `ServerInfo` entity is returned.
+  fn get_server_info(&mut self) -> Result<ServerInfo>;
+}
+
+// ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+// JSON Entities
+// ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The AccountsConfigInfo entity contains information about Gerrit configuration from the accounts section.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountsConfigInfo {
+  /// The value of the accounts.visibility parameter.
+  pub visibility: String,
+  /// The default display name of accounts, one of FULL_NAME, FIRST_NAME or USERNAME.
+  pub default_display_name: Option<String>,
+}
+
+/// The AuthInfo entity contains information about the authentication configuration of the Gerrit server.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthInfo {
+  /// The authentication type that is configured on the server, e.g. `LDAP`, `OAUTH` or `HTTP`.
+  pub auth_type: String,
+  /// Whether contributor agreements are required.
+  #[serde(default)]
+  pub use_contributor_agreements: bool,
+  /// List of account fields that are editable, e.g. `FULL_NAME`, `USER_NAME` or `REGISTER_NEW_EMAIL`.
+  pub editable_account_fields: Option<Vec<String>>,
+  /// The login URL. Only set if authentication type is HTTP or HTTP_LDAP.
+  pub login_url: Option<String>,
+  /// The login text. Only set if authentication type is HTTP or HTTP_LDAP.
+  pub login_text: Option<String>,
+  /// The URL to switch accounts.
+  pub switch_account_url: Option<String>,
+  /// The register URL. Only set if authentication type is LDAP.
+  pub register_url: Option<String>,
+  /// The register text. Only set if authentication type is LDAP.
+  pub register_text: Option<String>,
+  /// The URL to edit the full name. Only set if authentication type is LDAP.
+  pub edit_full_name_url: Option<String>,
+  /// The URL to obtain an HTTP password. Only set if authentication type is CUSTOM_EXTENSION.
+  pub http_password_url: Option<String>,
+  /// The policy to authenticate Git over HTTP and REST API requests, e.g. `HTTP` or `LDAP`.
+  pub git_basic_auth_policy: Option<String>,
+}
+
+/// The ChangeConfigInfo entity contains information about Gerrit configuration from the change section.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeConfigInfo {
+  /// Whether blame on side by side diff is allowed.
+  #[serde(default)]
+  pub allow_blame: bool,
+  /// Value of the change.largeChange parameter.
+  pub large_change: u32,
+  /// Label name for the reply button.
+  pub reply_label: String,
+  /// Tooltip for the reply button.
+  pub reply_tooltip: String,
+  /// How often in seconds the web interface should poll for updates to the currently open change.
+  pub update_delay: u32,
+  /// Whether changes with the same topic are submitted together.
+  #[serde(default)]
+  pub submit_whole_topic: bool,
+  /// Whether private changes are disabled.
+  #[serde(default)]
+  pub disable_private_changes: bool,
+  /// Behavior of the mergeability computation.
+  pub mergeability_computation_behavior: Option<String>,
+}
+
+/// The DownloadInfo entity contains information about supported download options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadInfo {
+  /// The supported download schemes as a map which maps the scheme name to a DownloadSchemeInfo entity.
+  pub schemes: BTreeMap<String, DownloadSchemeInfo>,
+  /// List of supported archive formats, e.g. `tgz`, `tar`, `tbz2` or `txz`.
+  pub archives: Vec<String>,
+}
+
+/// The DownloadSchemeInfo entity contains information about a supported download scheme and its commands.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadSchemeInfo {
+  /// The URL of the download scheme, where ${project} is used as placeholder for the project name.
+  pub url: String,
+  /// Whether this download scheme requires authentication.
+  #[serde(default)]
+  pub is_auth_required: bool,
+  /// Whether this download scheme supports authentication.
+  #[serde(default)]
+  pub is_auth_supported: bool,
+  /// Download commands as a map which maps the command name to the download command.
+  #[serde(default)]
+  pub commands: BTreeMap<String, String>,
+  /// Clone commands as a map which maps the command name to the clone command.
+  #[serde(default)]
+  pub clone_commands: BTreeMap<String, String>,
+}
+
+/// The GerritInfo entity contains information about Gerrit configuration from the gerrit section.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GerritInfo {
+  /// Name of the root project.
+  pub all_projects: String,
+  /// Name of the project in which meta data of all users is stored.
+  pub all_users: String,
+  /// Whether documentation search is available.
+  #[serde(default)]
+  pub doc_search: bool,
+  /// Custom base URL where Gerrit server documentation is located.
+  /// Documentation may also be available at /Documentation relative to the Gerrit base path.
+  pub doc_url: Option<String>,
+  /// Whether to enable the web UI for editing GPG keys.
+  #[serde(default)]
+  pub edit_gpg_keys: bool,
+  /// URL to report bugs.
+  pub report_bug_url: Option<String>,
+}
+
+/// The ServerInfo entity contains information about the configuration of the Gerrit server.
+///
+/// Only part of the sections returned by the server are modeled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerInfo {
+  /// Information about the configuration from the accounts section.
+  pub accounts: AccountsConfigInfo,
+  /// Information about the authentication configuration.
+  pub auth: AuthInfo,
+  /// Information about the configuration from the change section.
+  pub change: ChangeConfigInfo,
+  /// Information about the download configuration.
+  pub download: DownloadInfo,
+  /// Information about the configuration from the gerrit section.
+  pub gerrit: GerritInfo,
+  /// Information about the configuration from the user section.
+  pub user: UserConfigInfo,
+}
+
+/// The UserConfigInfo entity contains information about Gerrit configuration from the user section.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserConfigInfo {
+  /// The display name used for anonymous users.
+  pub anonymous_coward_name: String,
+}
@@ -2,21 +2,49 @@ use chrono::{DateTime, Utc};
 use serde_derive::{Deserialize, Serialize};
 
 ///////////////////////////////////////////////////////////////////////////////////////////////////
+/// Gerrit timestamp, given in UTC with nanosecond precision.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Timestamp(#[serde(with = "super::details::serde_timestamp")] pub DateTime<Utc>);
 
+impl Timestamp {
+  /// Create a Timestamp of the current time.
+  pub fn now() -> Self {
+    Timestamp(Utc::now())
+  }
+
+  /// Create a Timestamp from a chrono DateTime<Utc>.
+  pub fn from_chrono(datetime: DateTime<Utc>) -> Self {
+    Timestamp(datetime)
+  }
+
+  /// Parse a Timestamp from the Gerrit format "'yyyy-mm-dd hh:mm:ss.fffffffff'", given in UTC.
+  ///
+  /// The fractional seconds are optional.
+  pub fn parse(s: &str) -> Result<Self, chrono::ParseError> {
+    serde_timestamp::parse(s).map(Timestamp)
+  }
+
+  /// Get the chrono DateTime<Utc> of this Timestamp.
+  pub fn to_chrono(&self) -> DateTime<Utc> {
+    self.0
+  }
+}
+
 ///////////////////////////////////////////////////////////////////////////////////////////////////
 /// This uses the chrono crate to serialize and deserialize JSON data
 /// containing a Gerrit's custom timestamp format.
 /// The with attribute (as in #[serde(with="serde_timestamp")]) is used
 /// to provide the logic for handling the custom representation for DateTime<Utc>.
 pub mod serde_timestamp {
-  use chrono::{DateTime, TimeZone, Utc};
+  use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
   use serde::{self, Deserialize, Deserializer, Serializer};
 
   /// Gerrit timestamps are given in UTC and have the format "'yyyy-mm-dd hh:mm:ss.fffffffff'"
   /// where "'ffffffffff'" represents nanoseconds.
-  const GERRIT_FORMAT: &'static str = "%Y-%m-%d %H:%M:%S.%f";
+  const GERRIT_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.9f";
+
+  /// Format used for parsing, which also accepts timestamps without fractional seconds.
+  const GERRIT_PARSE_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
 
   /// Serialize a DateTime<Utc> using the GERRIT_FORMAT specified above.
   pub fn serialize<S>(date: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
@@ -33,8 +61,67 @@ pub mod serde_timestamp {
     D: Deserializer<'de>,
   {
     let s = String::deserialize(deserializer)?;
-    Utc
-      .datetime_from_str(&s, GERRIT_FORMAT)
-      .map_err(serde::de::Error::custom)
+    parse(&s).map_err(serde::de::Error::custom)
+  }
+
+  /// Parse a DateTime<Utc> using the GERRIT_PARSE_FORMAT specified above.
+  pub fn parse(s: &str) -> chrono::ParseResult<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(s, GERRIT_PARSE_FORMAT).map(|naive| Utc.from_utc_datetime(&naive))
+  }
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+/// Serialize an `Option<()>` query parameter as a bare flag, e.g. `?zip`.
+///
+/// Use together with `skip_serializing_none` so that `None` omits the parameter and `Some(())`
+/// sets it with an empty value, which Gerrit accepts as enabling the option.
+pub mod serde_flag {
+  use serde::Serializer;
+
+  /// Serialize a set flag as an empty value.
+  pub fn serialize<S>(_flag: &Option<()>, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    serializer.serialize_str("")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::{TimeZone, Timelike};
+
+  #[test]
+  fn timestamp_round_trip_keeps_nanoseconds() {
+    let json = r#""2020-01-02 03:04:05.123456789""#;
+    let timestamp: Timestamp = serde_json::from_str(json).unwrap();
+    assert_eq!(timestamp.to_chrono().nanosecond(), 123_456_789);
+    assert_eq!(serde_json::to_string(&timestamp).unwrap(), json);
+  }
+
+  #[test]
+  fn timestamp_with_zero_fraction() {
+    let json = r#""2020-01-02 03:04:05.000000000""#;
+    let timestamp: Timestamp = serde_json::from_str(json).unwrap();
+    assert_eq!(
+      timestamp,
+      Timestamp::from_chrono(Utc.with_ymd_and_hms(2020, 1, 2, 3, 4, 5).unwrap())
+    );
+    assert_eq!(serde_json::to_string(&timestamp).unwrap(), json);
+  }
+
+  #[test]
+  fn timestamp_without_fraction() {
+    let timestamp = Timestamp::parse("2020-01-02 03:04:05").unwrap();
+    assert_eq!(
+      timestamp.to_chrono(),
+      Utc.with_ymd_and_hms(2020, 1, 2, 3, 4, 5).unwrap()
+    );
+    assert_eq!(
+      serde_json::to_string(&timestamp).unwrap(),
+      r#""2020-01-02 03:04:05.000000000""#
+    );
+    assert!(Timestamp::parse("2020-01-02T03:04:05Z").is_err());
   }
 }
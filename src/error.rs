@@ -3,28 +3,233 @@ use std::fmt::Display;
 
 #[derive(Debug)]
 pub enum Error {
-  /// Unexpected HTTP response status code
-  UnexpectedHttpResponse(::http::StatusCode, Vec<u8>),
+  /// Unexpected HTTP response status code, with the response body and the request that failed
+  UnexpectedHttpResponse(::http::StatusCode, Vec<u8>, RequestInfo),
+  /// The HTTP response has a status code outside of the valid range 100-999, with the request that failed
+  InvalidStatusCode(u16, RequestInfo),
+  /// The requested resource was not found (HTTP 404), with the reason given by the server, the response body
+  /// and the request that failed
+  NotFound {
+    reason: String,
+    body: Vec<u8>,
+    request: RequestInfo,
+  },
+  /// The request conflicts with the current state of the resource (HTTP 409),
+  /// with the reason given by the server, the response body and the request that failed
+  Conflict {
+    reason: String,
+    body: Vec<u8>,
+    request: RequestInfo,
+  },
   /// Response is not JSON
   NotJsonResponse(Vec<u8>),
   /// Failed to deserialize JSON response
   InvalidJsonResponse(serde_json::Error),
+  /// Failed to decode base64 response
+  InvalidBase64Response(base64::DecodeError),
+  /// Response text is not valid UTF-8
+  InvalidUtf8Response(std::string::FromUtf8Error),
+  /// Failed to read or write a local file
+  Io(std::io::Error),
   /// The HTTP handler returned error
   HttpHandler(http::Error),
+  /// The request did not complete within the configured timeout
+  Timeout(http::Error),
   /// Failed to generate query parameters
   WrongQuery(String),
+  /// Request input failed validation before being sent
+  InvalidInput(String),
+  /// Response is missing a field that was expected to be set
+  MissingResponseField(String),
+  /// The asynchronous HTTP client returned error
+  #[cfg(feature = "async")]
+  AsyncHttp(reqwest::Error),
+}
+
+/// The method and URL of an HTTP request, kept in the errors of failed requests.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestInfo {
+  /// The request method, e.g. `GET`.
+  pub method: String,
+  /// The URL the request was sent to.
+  pub url: String,
+}
+
+impl Display for RequestInfo {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+    write!(f, "{} {}", self.method, self.url)
+  }
+}
+
+/// Cloneable summary of an Error.
+///
+/// The Error itself cannot be cloned as it holds the underlying source errors,
+/// so this keeps only the variant and the relevant details, e.g. for storing the last failure while retrying.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+  /// Unexpected HTTP response status code
+  UnexpectedHttpResponse(::http::StatusCode),
+  /// The HTTP response has a status code outside of the valid range 100-999
+  InvalidStatusCode(u16),
+  /// The requested resource was not found, with the reason given by the server
+  NotFound(String),
+  /// The request conflicts with the current state of the resource, with the reason given by the server
+  Conflict(String),
+  /// Response is not JSON
+  NotJsonResponse,
+  /// Failed to deserialize JSON response, with the error message
+  InvalidJsonResponse(String),
+  /// Failed to decode base64 response, with the error message
+  InvalidBase64Response(String),
+  /// Response text is not valid UTF-8, with the error message
+  InvalidUtf8Response(String),
+  /// Failed to read or write a local file
+  Io(std::io::ErrorKind),
+  /// The HTTP handler returned error, with the error message
+  HttpHandler(String),
+  /// The request did not complete within the configured timeout
+  Timeout,
+  /// Failed to generate query parameters, with the error message
+  WrongQuery(String),
+  /// Request input failed validation before being sent, with the error message
+  InvalidInput(String),
+  /// Response is missing a field that was expected to be set, with the field name
+  MissingResponseField(String),
+}
+
+impl Error {
+  /// Get a cloneable summary of this error.
+  pub fn kind(&self) -> ErrorKind {
+    match self {
+      Error::UnexpectedHttpResponse(code, ..) => ErrorKind::UnexpectedHttpResponse(*code),
+      Error::InvalidStatusCode(code, _) => ErrorKind::InvalidStatusCode(*code),
+      Error::NotFound { reason, .. } => ErrorKind::NotFound(reason.clone()),
+      Error::Conflict { reason, .. } => ErrorKind::Conflict(reason.clone()),
+      Error::NotJsonResponse(_) => ErrorKind::NotJsonResponse,
+      Error::InvalidJsonResponse(e) => ErrorKind::InvalidJsonResponse(e.to_string()),
+      Error::InvalidBase64Response(e) => ErrorKind::InvalidBase64Response(e.to_string()),
+      Error::InvalidUtf8Response(e) => ErrorKind::InvalidUtf8Response(e.to_string()),
+      Error::Io(e) => ErrorKind::Io(e.kind()),
+      Error::HttpHandler(e) => ErrorKind::HttpHandler(e.to_string()),
+      Error::Timeout(_) => ErrorKind::Timeout,
+      Error::WrongQuery(s) => ErrorKind::WrongQuery(s.clone()),
+      Error::InvalidInput(s) => ErrorKind::InvalidInput(s.clone()),
+      Error::MissingResponseField(s) => ErrorKind::MissingResponseField(s.clone()),
+      #[cfg(feature = "async")]
+      Error::AsyncHttp(e) => ErrorKind::HttpHandler(e.to_string()),
+    }
+  }
+
+  /// Whether the requested resource was not found.
+  pub fn is_not_found(&self) -> bool {
+    matches!(self, Error::NotFound { .. })
+  }
+
+  /// Whether the request conflicts with the current state of the resource,
+  /// e.g. abandoning a change that is already merged.
+  pub fn is_conflict(&self) -> bool {
+    matches!(self, Error::Conflict { .. })
+  }
+
+  /// Get the body of the HTTP response that caused this error, if any.
+  pub fn response_body(&self) -> Option<&[u8]> {
+    match self {
+      Error::UnexpectedHttpResponse(_, body, _)
+      | Error::NotFound { body, .. }
+      | Error::Conflict { body, .. }
+      | Error::NotJsonResponse(body) => Some(body),
+      _ => None,
+    }
+  }
+
+  /// Get the method and URL of the HTTP request that caused this error, if any.
+  pub fn request(&self) -> Option<&RequestInfo> {
+    match self {
+      Error::UnexpectedHttpResponse(_, _, request)
+      | Error::InvalidStatusCode(_, request)
+      | Error::NotFound { request, .. }
+      | Error::Conflict { request, .. } => Some(request),
+      _ => None,
+    }
+  }
+
+  /// Get a displayable form of this error with extra details according to the verbosity level.
+  ///
+  /// Level 0 is the same as the plain Display output.
+  /// Level 1 adds the response body of HTTP failures and the detail messages of query and handler failures.
+  /// Level 2 and above add the method and URL of the failed request and the chain of underlying source errors
+  /// as well.
+  pub fn verbose(&self, verbosity: u8) -> VerboseError<'_> {
+    VerboseError { error: self, verbosity }
+  }
+}
+
+/// Display adapter of an Error created by [Error::verbose](enum.Error.html#method.verbose).
+pub struct VerboseError<'a> {
+  error: &'a Error,
+  verbosity: u8,
+}
+
+impl Display for VerboseError<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+    write!(f, "{}", self.error)?;
+    if self.verbosity >= 1 {
+      if let Some(body) = self.error.response_body().filter(|body| !body.is_empty()) {
+        write!(f, "\nResponse body:\n{}", String::from_utf8_lossy(body).trim_end())?;
+      }
+      match self.error {
+        Error::HttpHandler(e) if self.verbosity == 1 => write!(f, ": {}", e)?,
+        Error::WrongQuery(s) => write!(f, ": {}", s)?,
+        _ => {}
+      }
+    }
+    if self.verbosity >= 2 {
+      if let Some(request) = self.error.request() {
+        write!(f, "\nRequest: {}", request)?;
+      }
+      let mut source = std::error::Error::source(self.error);
+      while let Some(e) = source {
+        write!(f, "\nCaused by: {}", e)?;
+        source = e.source();
+      }
+    }
+    Ok(())
+  }
 }
 
 impl Display for Error {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
     match self {
-      Error::UnexpectedHttpResponse(code, _) => {
-        write!(f, "Unexpected HTTP response code: {}", code)
+      Error::UnexpectedHttpResponse(code, body, _) => {
+        write!(f, "Unexpected HTTP response code: {}", code)?;
+        let body = String::from_utf8_lossy(body);
+        match body.trim().lines().next() {
+          Some(reason) => write!(f, ": {}", reason),
+          None => Ok(()),
+        }
+      }
+      Error::InvalidStatusCode(code, _) => write!(f, "Invalid HTTP response status code: {}", code),
+      Error::NotFound { reason, .. } => write!(f, "Not found: {}", reason),
+      Error::Conflict { reason, .. } => write!(f, "Conflict: {}", reason),
+      Error::NotJsonResponse(body) => {
+        f.write_str("Unexpected non-JSON response")?;
+        let body = String::from_utf8_lossy(body);
+        match body.trim().lines().next() {
+          Some(line) => write!(f, ": {}", line),
+          None => Ok(()),
+        }
       }
-      Error::NotJsonResponse(_) => f.write_str("Unexpected non-JSON response"),
       Error::InvalidJsonResponse(e) => write!(f, "Failed to parse JSON response:\n {}", e),
+      Error::InvalidBase64Response(e) => write!(f, "Failed to decode base64 response:\n {}", e),
+      Error::InvalidUtf8Response(e) => write!(f, "Response is not valid UTF-8:\n {}", e),
+      Error::Io(e) => write!(f, "I/O failure: {}", e),
       Error::HttpHandler(_) => f.write_str("Low-level HTTP Handler failure"),
+      Error::Timeout(_) => f.write_str("Request timed out"),
       Error::WrongQuery(_) => f.write_str("Failed to generate query"),
+      Error::InvalidInput(s) => write!(f, "Invalid input: {}", s),
+      Error::MissingResponseField(s) => write!(f, "Response is missing field '{}'", s),
+      #[cfg(feature = "async")]
+      Error::AsyncHttp(_) => f.write_str("Asynchronous HTTP client failure"),
     }
   }
 }
@@ -33,10 +238,21 @@ impl std::error::Error for Error {
   fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
     match *self {
       Error::UnexpectedHttpResponse(..) => None,
+      Error::InvalidStatusCode(..) => None,
+      Error::NotFound { .. } => None,
+      Error::Conflict { .. } => None,
       Error::NotJsonResponse(_) => None,
       Error::InvalidJsonResponse(ref e) => Some(e),
+      Error::InvalidBase64Response(ref e) => Some(e),
+      Error::InvalidUtf8Response(ref e) => Some(e),
+      Error::Io(ref e) => Some(e),
       Error::HttpHandler(ref e) => Some(e),
+      Error::Timeout(ref e) => Some(e),
       Error::WrongQuery(_) => None,
+      Error::InvalidInput(_) => None,
+      Error::MissingResponseField(_) => None,
+      #[cfg(feature = "async")]
+      Error::AsyncHttp(ref e) => Some(e),
     }
   }
 }
@@ -47,9 +263,38 @@ impl From<serde_json::Error> for Error {
   }
 }
 
+impl From<base64::DecodeError> for Error {
+  fn from(e: base64::DecodeError) -> Self {
+    Error::InvalidBase64Response(e)
+  }
+}
+
+impl From<std::string::FromUtf8Error> for Error {
+  fn from(e: std::string::FromUtf8Error) -> Self {
+    Error::InvalidUtf8Response(e)
+  }
+}
+
+impl From<std::io::Error> for Error {
+  fn from(e: std::io::Error) -> Self {
+    Error::Io(e)
+  }
+}
+
 impl From<http::Error> for Error {
   fn from(e: http::Error) -> Self {
-    Error::HttpHandler(e)
+    if e.is_timeout() {
+      Error::Timeout(e)
+    } else {
+      Error::HttpHandler(e)
+    }
+  }
+}
+
+#[cfg(feature = "async")]
+impl From<reqwest::Error> for Error {
+  fn from(e: reqwest::Error) -> Self {
+    Error::AsyncHttp(e)
   }
 }
 
@@ -0,0 +1,239 @@
+//! Groups related REST endpoints.
+//!
+//! See [GroupEndpoints](trait.GroupEndpoints.html) trait for the REST API.
+
+use crate::accounts::AccountInfo;
+use crate::details::Timestamp;
+use crate::Result;
+use serde_derive::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::collections::BTreeMap;
+
+// ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+// REST API
+// ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// This trait describes the group related REST endpoints.
+///
+/// A `group_id` identifies a group and can be given as the UUID of the group, the legacy numeric ID
+/// of the group, or the name of the group if it is unique.
+pub trait GroupEndpoints {
+  /// Lists the groups accessible by the caller.
+  ///
+  /// This is the same as using the ls-groups command over SSH, and accepts the same options as query parameters.
+  ///
+  /// As result a map is returned that maps the group names to `GroupInfo` entries.
+  /// The entries in the map are sorted by group name.
+  fn list_groups(&mut self, params: &GroupQueryParams) -> Result<BTreeMap<String, GroupInfo>>;
+
+  /// Retrieves a group.
+  ///
+  /// As response a `GroupInfo` entity is returned that describes the group.
+  fn get_group(&mut self, group_id: &str) -> Result<GroupInfo>;
+
+  /// Creates a new Gerrit internal group.
+  ///
+  /// In the request body additional data for the group can be provided as `GroupInput`.
+  ///
+  /// As response the `GroupInfo` entity is returned that describes the created group.
+  fn create_group(&mut self, name: &str, input: &GroupInput) -> Result<GroupInfo>;
+
+  /// Retrieves a group with the direct members and the directly included groups.
+  ///
+  /// As response a `GroupInfo` entity is returned that describes the group.
+  fn get_group_detail(&mut self, group_id: &str) -> Result<GroupInfo>;
+
+  /// Renames a Gerrit internal group.
+  ///
+  /// As response the new group name is returned.
+  fn rename_group(&mut self, group_id: &str, name: &str) -> Result<String>;
+
+  /// Sets the description of a Gerrit internal group.
+  ///
+  /// As response the new group description is returned.
+  /// If the description was deleted, `None` is returned.
+  fn set_group_description(&mut self, group_id: &str, description: Option<&str>) -> Result<Option<String>>;
+
+  /// Lists the direct members of a Gerrit internal group.
+  ///
+  /// If `recursive` is set, the members of all included groups are listed as well.
+  ///
+  /// As result a list of `AccountInfo` entries is returned. The entries in the list are sorted by full name,
+  /// preferred email and id.
+  fn list_members(&mut self, group_id: &str, recursive: bool) -> Result<Vec<AccountInfo>>;
+
+  /// Adds a user as member to a Gerrit internal group.
+  ///
+  /// As response an `AccountInfo` entity is returned that describes the group member.
+  fn add_member(&mut self, group_id: &str, account_id: &str) -> Result<AccountInfo>;
+
+  /// Adds one or several users to a Gerrit internal group.
+  ///
+  /// The users to be added to the group must be provided in the request body as a `MembersInput` entity.
+  ///
+  /// As response a list of detailed `AccountInfo` entities is returned that describes the group members
+  /// that were specified in the `MembersInput`. An `AccountInfo` entity is returned for each user specified
+  /// in the input, independently of whether the user was newly added to the group or whether the user was
+  /// already a member of the group.
+  fn add_members(&mut self, group_id: &str, input: &MembersInput) -> Result<Vec<AccountInfo>>;
+
+  /// Removes a user from a Gerrit internal group.
+  fn remove_member(&mut self, group_id: &str, account_id: &str) -> Result<()>;
+
+  /// Removes one or several users from a Gerrit internal group.
+  ///
+  /// The users to be removed from the group must be provided in the request body as a `MembersInput` entity.
+  fn remove_members(&mut self, group_id: &str, input: &MembersInput) -> Result<()>;
+}
+
+// ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+// JSON Entities
+// ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The GroupBaseInfo entity contains base information about the group.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupBaseInfo {
+  /// The UUID of the group.
+  pub id: String,
+  /// The name of the group.
+  pub name: String,
+}
+
+/// The GroupDescriptionInput entity contains information for setting a description.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupDescriptionInput {
+  /// The new description of the group.
+  /// If not set, the description is removed.
+  pub description: Option<String>,
+}
+
+/// The GroupInfo entity contains information about a group.
+/// This can be a Gerrit internal group, or an external group that is known to Gerrit.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupInfo {
+  /// The URL encoded UUID of the group.
+  pub id: String,
+  /// The name of the group.
+  /// For external groups the name is missing if there is no group backend that can resolve the group UUID.
+  /// E.g. this is the case when a plugin that provided a group backend was uninstalled.
+  /// Not set if returned in a map where the group name is used as map key.
+  pub name: Option<String>,
+  /// URL to information about the group. Typically a URL to a web page that permits users to apply
+  /// to join the group, or manage their membership.
+  pub url: Option<String>,
+  /// Options of the group.
+  pub options: GroupOptionsInfo,
+  /// The description of the group.
+  pub description: Option<String>,
+  /// The numeric ID of the group. Only set for Gerrit internal groups.
+  pub group_id: Option<u32>,
+  /// The name of the owner group. Only set for Gerrit internal groups.
+  pub owner: Option<String>,
+  /// The URL encoded UUID of the owner group. Only set for Gerrit internal groups.
+  pub owner_id: Option<String>,
+  /// The timestamp of when the group was created. Only set for Gerrit internal groups.
+  pub created_on: Option<Timestamp>,
+  /// Whether the query would deliver more results if not limited.
+  /// Only set on the last group that is returned by a group query.
+  #[serde(default, rename = "_more_groups")]
+  pub more_groups: bool,
+  /// A list of AccountInfo entities describing the direct members.
+  /// Only set if members are requested.
+  pub members: Option<Vec<AccountInfo>>,
+  /// A list of GroupInfo entities describing the direct subgroups.
+  /// Only set if subgroups are requested.
+  pub includes: Option<Vec<GroupInfo>>,
+}
+
+/// The GroupInput entity contains information for the creation of a new internal group.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GroupInput {
+  /// The name of the group (not encoded).
+  /// If set, must match the group name in the URL.
+  pub name: Option<String>,
+  /// The UUID of the group.
+  pub uuid: Option<String>,
+  /// The description of the group.
+  pub description: Option<String>,
+  /// Whether the group is visible to all registered users.
+  /// False if not set.
+  pub visible_to_all: Option<bool>,
+  /// The URL encoded ID of the owner group.
+  /// This can be a group UUID, a legacy numeric group ID or a unique group name.
+  /// If not set, the new group will be self-owned.
+  pub owner_id: Option<String>,
+  /// The initial members in a list of account ids.
+  pub members: Option<Vec<String>>,
+}
+
+/// The GroupNameInput entity contains information for renaming a group.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupNameInput {
+  /// The new group name.
+  pub name: String,
+}
+
+/// Options of the group.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupOptionsInfo {
+  /// Whether the group is visible to all registered users.
+  #[serde(default)]
+  pub visible_to_all: bool,
+}
+
+/// The MembersInput entity contains information about accounts that should be added as members
+/// to a group or that should be deleted from the group.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MembersInput {
+  /// A list of account ids that identify the accounts that should be added or deleted.
+  pub members: Vec<String>,
+}
+
+// ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+// OPTIONS
+// ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Query parameters available for the list groups endpoint.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GroupQueryParams {
+  /// Additional fields can be obtained by adding `o` parameters.
+  #[serde(rename = "o")]
+  pub additional_opts: Option<Vec<GroupOpt>>,
+  /// Limit the results to the groups that are owned by the calling user.
+  #[serde(serialize_with = "crate::details::serde_flag::serialize")]
+  pub owned: Option<()>,
+  /// Limit the results to the groups that are visible to all registered users.
+  #[serde(rename = "visible-to-all")]
+  #[serde(serialize_with = "crate::details::serde_flag::serialize")]
+  pub visible_to_all: Option<()>,
+  /// Limit the results to the groups that contain the given user.
+  #[serde(rename = "u")]
+  pub user: Option<String>,
+  /// Limit the results to those groups that match the specified regex.
+  #[serde(rename = "r")]
+  pub regex: Option<String>,
+  /// Limit the results to those groups that match the specified substring.
+  #[serde(rename = "m")]
+  pub substring: Option<String>,
+  /// Limit the number of groups to be included in the results.
+  #[serde(rename = "n")]
+  pub limit: Option<u32>,
+  /// Skip the given number of groups from the beginning of the list.
+  #[serde(rename = "S")]
+  pub start: Option<u32>,
+}
+
+/// Additional fields can be obtained by adding `o` parameters to the list groups endpoint.
+#[derive(AsRefStr, Display, PartialEq, Eq, Clone, Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum GroupOpt {
+  /// Includes the direct subgroups of each group.
+  Includes,
+  /// Includes the direct members of each group.
+  Members,
+}
@@ -1,34 +1,128 @@
-use crate::error::Error;
-use crate::http::{Header, HttpRequestHandler};
+use crate::error::{Error, RequestInfo};
+use crate::http::{Header, HttpRequestHandler, Transport};
 use http::StatusCode;
+use log::debug;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::time::Duration;
 
 type Result<T> = std::result::Result<T, crate::error::Error>;
 
-pub struct RestHandler {
-  http: HttpRequestHandler,
+/// Policy for retrying requests that failed with a transient error.
+///
+/// Requests answered with a server error (5xx) or `429 Too Many Requests` are retried with exponential backoff,
+/// starting at `base_delay` and doubling on each attempt up to `max_delay`.
+/// If the response has a `Retry-After` header given in seconds, that delay is used instead, capped at `max_delay`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryConfig {
+  /// Maximum number of retries after the first attempt.
+  pub max_retries: u32,
+  /// Delay before the first retry.
+  pub base_delay: Duration,
+  /// Upper bound for the delay between attempts.
+  pub max_delay: Duration,
+  /// Whether to also retry the non-idempotent POST, PUT and DELETE requests.
+  /// Only GET requests are retried by default.
+  pub retry_non_idempotent: bool,
 }
 
-impl RestHandler {
-  pub fn new(http: HttpRequestHandler) -> Self {
-    Self { http }
+impl Default for RetryConfig {
+  fn default() -> Self {
+    Self {
+      max_retries: 3,
+      base_delay: Duration::from_millis(500),
+      max_delay: Duration::from_secs(30),
+      retry_non_idempotent: false,
+    }
+  }
+}
+
+impl RetryConfig {
+  /// Get the delay before retrying `response` on the given (0-based) retry attempt,
+  /// or None if it should not be retried.
+  fn delay(&self, attempt: u32, idempotent: bool, response: &Response) -> Option<Duration> {
+    let retryable = response.code.is_server_error() || response.code == StatusCode::TOO_MANY_REQUESTS;
+    if !retryable || attempt >= self.max_retries || !(idempotent || self.retry_non_idempotent) {
+      return None;
+    }
+    let delay = match response.header("Retry-After").and_then(|s| s.parse::<u64>().ok()) {
+      Some(secs) => Duration::from_secs(secs),
+      None => self
+        .base_delay
+        .checked_mul(1 << attempt.min(31))
+        .unwrap_or(self.max_delay),
+    };
+    Some(delay.min(self.max_delay))
+  }
+}
+
+/// Characters that are percent-encoded in a URL path segment: all but the unreserved ones.
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+/// Percent-encode `s` to be used as a single URL path segment.
+///
+/// Slashes are encoded too, e.g. the project name `platform/build` becomes `platform%2Fbuild`.
+pub(crate) fn encode_path_segment(s: &str) -> String {
+  utf8_percent_encode(s, PATH_SEGMENT).to_string()
+}
+
+/// Build the URL of `path` with `query` serialized as the query string.
+///
+/// The `?` separator is only appended to `path` if the query has any parameters.
+pub(crate) fn url_with_query<Q>(path: &str, query: &Q) -> Result<String>
+where
+  Q: Serialize + ?Sized,
+{
+  let params = serde_url_params::to_string(query)?;
+  if params.is_empty() {
+    Ok(path.to_string())
+  } else {
+    Ok(format!("{}?{}", path, params))
+  }
+}
+
+pub struct RestHandler<H: Transport = HttpRequestHandler> {
+  http: H,
+  retry: Option<RetryConfig>,
+}
+
+impl<H: Transport> RestHandler<H> {
+  pub fn new(http: H) -> Self {
+    Self { http, retry: None }
+  }
+
+  /// Set the policy for retrying requests that failed with a transient error.
+  pub fn retry(mut self, retry: Option<RetryConfig>) -> Self {
+    self.retry = retry;
+    self
+  }
+
+  /// Reconfigure the underlying HTTP transport, keeping the rest of the configuration.
+  pub fn map_http<F>(mut self, f: F) -> Result<Self>
+  where
+    F: FnOnce(H) -> std::result::Result<H, crate::http::Error>,
+  {
+    self.http = f(self.http)?;
+    Ok(self)
   }
 
   pub fn get(&mut self, url: &str) -> Result<Response> {
-    self.http.headers(&[/*Header::AcceptAppJson*/])?;
-    let (code, message) = self.http.get(url)?;
-    Ok(Response {
-      code: StatusCode::from_u16(code as u16).unwrap(),
-      message: message.into(),
-    })
+    self.http.headers(&[Header::AcceptAppJson])?;
+    self.perform("GET", url, true, |http| http.get(url))
+  }
+
+  /// Perform a GET request with `query` serialized as the URL query string, see `url_with_query`.
+  pub fn get_with_query<Q>(&mut self, path: &str, query: &Q) -> Result<Response>
+  where
+    Q: Serialize + ?Sized,
+  {
+    self.get(&url_with_query(path, query)?)
   }
 
   pub fn put(&mut self, url: &str) -> Result<Response> {
-    let (code, message) = self.http.post(url, None)?;
-    Ok(Response {
-      code: StatusCode::from_u16(code as u16).unwrap(),
-      message: message.into(),
-    })
+    self.http.headers(&[])?;
+    self.perform("PUT", url, false, |http| http.put(url, None))
   }
 
   pub fn put_json<T>(&mut self, url: &str, data: &T) -> Result<Response>
@@ -39,11 +133,12 @@ impl RestHandler {
       .http
       .headers(&[Header::ContentTypeAppJson /*, Header::AcceptAppJson*/])?;
     let data = serde_json::to_string(data)?;
-    let (code, message) = self.http.put(url, Some(data.as_bytes()))?;
-    Ok(Response {
-      code: StatusCode::from_u16(code as u16).unwrap(),
-      message: message.into(),
-    })
+    self.perform("PUT", url, false, |http| http.put(url, Some(data.as_bytes())))
+  }
+
+  pub fn put_raw(&mut self, url: &str, data: &[u8]) -> Result<Response> {
+    self.http.headers(&[Header::ContentTypeOctetStream])?;
+    self.perform("PUT", url, false, |http| http.put(url, Some(data)))
   }
 
   pub fn post_json<T>(&mut self, url: &str, data: &T) -> Result<Response>
@@ -54,52 +149,150 @@ impl RestHandler {
       .http
       .headers(&[Header::ContentTypeAppJson /*, Header::AcceptAppJson*/])?;
     let data = serde_json::to_string(data)?;
-    let (code, message) = self.http.post(url, Some(data.as_bytes()))?;
-    Ok(Response {
-      code: StatusCode::from_u16(code as u16).unwrap(),
-      message: message.into(),
-    })
+    self.perform("POST", url, false, |http| http.post(url, Some(data.as_bytes())))
+  }
+
+  pub fn post_text(&mut self, url: &str, data: &str) -> Result<Response> {
+    self.http.headers(&[Header::ContentTypeTextPlain])?;
+    self.perform("POST", url, false, |http| http.post(url, Some(data.as_bytes())))
   }
 
   pub fn post(&mut self, url: &str) -> Result<Response> {
-    let (code, message) = self.http.post(url, None)?;
-    Ok(Response {
-      code: StatusCode::from_u16(code as u16).unwrap(),
-      message: message.into(),
-    })
+    self.http.headers(&[])?;
+    self.perform("POST", url, false, |http| http.post(url, None))
   }
 
   pub fn delete(&mut self, url: &str) -> Result<Response> {
-    self.http.headers(&[/*Header::AcceptAppJson*/])?;
-    let (code, message) = self.http.delete(url)?;
-    Ok(Response {
-      code: StatusCode::from_u16(code as u16).unwrap(),
-      message: message.into(),
-    })
+    self.http.headers(&[Header::AcceptAppJson])?;
+    self.perform("DELETE", url, false, |http| http.delete(url))
   }
 
-  pub fn http(self) -> HttpRequestHandler {
+  /// Perform a GET request that only returns the resource if its ETag differs from `etag`.
+  ///
+  /// The response code is `304 Not Modified` if the resource did not change.
+  pub fn get_if_none_match(&mut self, url: &str, etag: &str) -> Result<Response> {
+    self
+      .http
+      .headers(&[Header::AcceptAppJson, Header::IfNoneMatch(etag.into())])?;
+    self.perform("GET", url, true, |http| http.get(url))
+  }
+
+  pub fn http(self) -> H {
     self.http
   }
+
+  /// Perform the request, retrying it according to the retry policy.
+  fn perform<F>(&mut self, method: &str, url: &str, idempotent: bool, mut request: F) -> Result<Response>
+  where
+    F: FnMut(&mut H) -> std::result::Result<(u16, Vec<u8>), crate::http::Error>,
+  {
+    let mut attempt = 0;
+    loop {
+      let (code, message) = request(&mut self.http)?;
+      let request_info = RequestInfo {
+        method: method.into(),
+        url: self.http.url(url),
+      };
+      let response = self.response(code, message, request_info)?;
+      let delay = match &self.retry {
+        Some(retry) => retry.delay(attempt, idempotent, &response),
+        None => None,
+      };
+      match delay {
+        Some(delay) => {
+          debug!("retrying request after {} response in {:?}", response.code, delay);
+          std::thread::sleep(delay);
+          attempt += 1;
+        }
+        None => return Ok(response),
+      }
+    }
+  }
+
+  fn response(&self, code: u16, message: Vec<u8>, request: RequestInfo) -> Result<Response> {
+    let code = match StatusCode::from_u16(code) {
+      Ok(code) => code,
+      Err(_) => return Err(Error::InvalidStatusCode(code, request)),
+    };
+    Ok(Response {
+      code,
+      headers: self.http.response_headers().to_vec(),
+      message: message.into(),
+      request,
+    })
+  }
 }
 
 pub struct Response {
   pub code: http::StatusCode,
+  /// Response headers as name and value pairs.
+  pub headers: Vec<(String, String)>,
   pub message: Message,
+  /// The method and URL of the request this is the response to.
+  pub request: RequestInfo,
 }
 
 impl Response {
+  /// Get the value of the first response header with the given name, compared case-insensitively.
+  pub fn header(&self, name: &str) -> Option<&str> {
+    self
+      .headers
+      .iter()
+      .find(|(key, _)| key.eq_ignore_ascii_case(name))
+      .map(|(_, value)| value.as_str())
+  }
+
+  /// Get the ETag of the returned resource, if any.
+  pub fn etag(&self) -> Option<&str> {
+    self.header("ETag")
+  }
+
   pub fn expect(self, expected_code: http::StatusCode) -> Result<Message> {
     Ok(self.expect_or(expected_code)?.message)
   }
 
+  /// Check that the response has the expected code and deserialize its JSON body.
+  pub fn expect_json<T>(self, expected_code: http::StatusCode) -> Result<T>
+  where
+    T: DeserializeOwned,
+  {
+    let json = self.expect(expected_code)?.json()?;
+    Ok(serde_json::from_str(&json)?)
+  }
+
   pub fn expect_or(self, expected_code: http::StatusCode) -> Result<Self> {
     if self.code.as_u16() != expected_code.as_u16() {
-      Err(Error::UnexpectedHttpResponse(self.code, self.message.raw()))
+      Err(self.into_error())
     } else {
       Ok(self)
     }
   }
+
+  /// Convert this unexpected response into an error.
+  ///
+  /// A `404 Not Found` response becomes `Error::NotFound` and a `409 Conflict` response becomes
+  /// `Error::Conflict`, both with the reason given by the server. Any other becomes `Error::UnexpectedHttpResponse`.
+  pub fn into_error(self) -> Error {
+    match self.code {
+      StatusCode::NOT_FOUND => Error::NotFound {
+        reason: self.reason().trim_start_matches("Not found: ").into(),
+        body: self.message.raw(),
+        request: self.request,
+      },
+      StatusCode::CONFLICT => Error::Conflict {
+        reason: self.reason(),
+        body: self.message.raw(),
+        request: self.request,
+      },
+      _ => Error::UnexpectedHttpResponse(self.code, self.message.raw(), self.request),
+    }
+  }
+
+  /// Get the reason of a failure given by the server, i.e. the first line of the response body.
+  fn reason(&self) -> String {
+    let body = String::from_utf8_lossy(&self.message.0);
+    body.trim().lines().next().unwrap_or_default().into()
+  }
 }
 
 pub struct Message(Vec<u8>);
@@ -114,7 +307,7 @@ impl Message {
   }
 
   pub fn json(self) -> Result<String> {
-    const MAGIC_PREFIX: &'static [u8] = b")]}'\n";
+    const MAGIC_PREFIX: &[u8] = b")]}'\n";
     if !self.0.as_slice().starts_with(MAGIC_PREFIX) {
       return Err(Error::NotJsonResponse(self.raw()));
     }
@@ -128,3 +321,186 @@ impl From<Vec<u8>> for Message {
     Self(s)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::http::mock::MockTransport;
+
+  fn no_delay() -> RetryConfig {
+    RetryConfig {
+      base_delay: Duration::from_millis(0),
+      ..RetryConfig::default()
+    }
+  }
+
+  #[test]
+  fn html_response_is_not_json() {
+    let html = "<!DOCTYPE html>\n<html><head><title>Sign In</title></head></html>\n";
+    let mock = MockTransport::new().respond(200, html);
+    let mut rest = RestHandler::new(mock.clone());
+    let error = rest
+      .get("a/config/server/version")
+      .unwrap()
+      .expect(StatusCode::OK)
+      .unwrap()
+      .json()
+      .unwrap_err();
+    assert!(matches!(error, Error::NotJsonResponse(ref body) if body == html.as_bytes()));
+    assert_eq!(error.to_string(), "Unexpected non-JSON response: <!DOCTYPE html>");
+    assert_eq!(mock.requests()[0].headers, vec![Header::AcceptAppJson]);
+  }
+
+  #[test]
+  fn invalid_status_code_is_an_error() {
+    let mock = MockTransport::new().respond(1000, "").respond(0, "");
+    let mut rest = RestHandler::new(mock);
+    for code in &[1000, 0] {
+      let error = rest.get("a/changes/").err().unwrap();
+      assert!(matches!(error, Error::InvalidStatusCode(c, _) if c == *code));
+      assert_eq!(error.kind(), crate::error::ErrorKind::InvalidStatusCode(*code));
+    }
+  }
+
+  #[test]
+  fn retry_until_success() {
+    let mock = MockTransport::new()
+      .respond(503, "")
+      .respond(503, "")
+      .respond(200, "ok");
+    let mut rest = RestHandler::new(mock.clone()).retry(Some(no_delay()));
+    let response = rest.get("a/changes/").unwrap();
+    assert_eq!(response.code, StatusCode::OK);
+    assert_eq!(mock.requests().len(), 3);
+  }
+
+  #[test]
+  fn retry_gives_up_after_max_retries() {
+    let mut mock = MockTransport::new();
+    for _ in 0..3 {
+      mock = mock.respond(500, "");
+    }
+    let config = RetryConfig {
+      max_retries: 2,
+      ..no_delay()
+    };
+    let mut rest = RestHandler::new(mock.clone()).retry(Some(config));
+    let response = rest.get("a/changes/").unwrap();
+    assert_eq!(response.code, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(mock.requests().len(), 3);
+  }
+
+  #[test]
+  fn post_is_not_retried_by_default() {
+    let mock = MockTransport::new().respond(503, "");
+    let mut rest = RestHandler::new(mock.clone()).retry(Some(no_delay()));
+    let response = rest.post("a/changes/1/abandon").unwrap();
+    assert_eq!(response.code, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(mock.requests().len(), 1);
+  }
+
+  #[test]
+  fn client_errors_are_not_retried() {
+    let mock = MockTransport::new().respond(400, "");
+    let mut rest = RestHandler::new(mock.clone()).retry(Some(no_delay()));
+    assert_eq!(rest.get("a/changes/").unwrap().code, StatusCode::BAD_REQUEST);
+    assert_eq!(mock.requests().len(), 1);
+  }
+
+  fn response(code: StatusCode, headers: &[(&str, &str)]) -> Response {
+    Response {
+      code,
+      headers: headers.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+      message: Vec::new().into(),
+      request: RequestInfo::default(),
+    }
+  }
+
+  fn response_with_body(code: StatusCode, body: &str) -> Response {
+    Response {
+      message: body.as_bytes().to_vec().into(),
+      ..response(code, &[])
+    }
+  }
+
+  #[test]
+  fn not_found_keeps_reason_and_body() {
+    let error = response_with_body(StatusCode::NOT_FOUND, "Not found: 1234\n").into_error();
+    assert!(error.is_not_found());
+    assert_eq!(error.to_string(), "Not found: 1234");
+    assert_eq!(error.response_body(), Some(&b"Not found: 1234\n"[..]));
+    assert_eq!(error.kind(), crate::error::ErrorKind::NotFound("1234".into()));
+  }
+
+  #[test]
+  fn conflict_keeps_reason_and_body() {
+    let error = response_with_body(StatusCode::CONFLICT, "change is merged\n").into_error();
+    assert!(error.is_conflict());
+    assert_eq!(error.to_string(), "Conflict: change is merged");
+    assert_eq!(error.response_body(), Some(&b"change is merged\n"[..]));
+    assert_eq!(
+      error.kind(),
+      crate::error::ErrorKind::Conflict("change is merged".into())
+    );
+  }
+
+  #[test]
+  fn retry_delay_backs_off_exponentially() {
+    let config = RetryConfig::default();
+    let unavailable = response(StatusCode::SERVICE_UNAVAILABLE, &[]);
+    assert_eq!(config.delay(0, true, &unavailable), Some(Duration::from_millis(500)));
+    assert_eq!(config.delay(2, true, &unavailable), Some(Duration::from_secs(2)));
+    assert_eq!(config.delay(3, true, &unavailable), None);
+    assert_eq!(config.delay(0, false, &unavailable), None);
+  }
+
+  #[test]
+  fn retry_after_is_capped_at_max_delay() {
+    let config = RetryConfig::default();
+    let short = response(StatusCode::TOO_MANY_REQUESTS, &[("Retry-After", "5")]);
+    assert_eq!(config.delay(0, true, &short), Some(Duration::from_secs(5)));
+    let long = response(StatusCode::TOO_MANY_REQUESTS, &[("retry-after", "120")]);
+    assert_eq!(config.delay(0, true, &long), Some(config.max_delay));
+  }
+
+  #[test]
+  fn verbose_error_shows_request() {
+    let mock = MockTransport::new()
+      .respond(404, "Not found: 1234\n")
+      .respond(500, "Internal server error\n")
+      .respond(1000, "");
+    let mut rest = RestHandler::new(mock);
+    let error = rest
+      .get("a/changes/1234")
+      .unwrap()
+      .expect(StatusCode::OK)
+      .err()
+      .unwrap();
+    assert_eq!(
+      error.request(),
+      Some(&RequestInfo {
+        method: "GET".into(),
+        url: "a/changes/1234".into(),
+      })
+    );
+    assert_eq!(
+      error.verbose(1).to_string(),
+      "Not found: 1234\nResponse body:\nNot found: 1234"
+    );
+    assert_eq!(
+      error.verbose(2).to_string(),
+      "Not found: 1234\nResponse body:\nNot found: 1234\nRequest: GET a/changes/1234"
+    );
+    let error = rest.post("a/changes/1234/submit").unwrap().into_error();
+    assert_eq!(
+      error.verbose(2).to_string(),
+      "Unexpected HTTP response code: 500 Internal Server Error: Internal server error\n\
+       Response body:\nInternal server error\nRequest: POST a/changes/1234/submit"
+    );
+    let error = rest.delete("a/changes/1234").err().unwrap();
+    assert_eq!(
+      error.verbose(2).to_string(),
+      "Invalid HTTP response status code: 1000\nRequest: DELETE a/changes/1234"
+    );
+  }
+}
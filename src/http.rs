@@ -2,6 +2,8 @@ use curl::easy::Easy as CurlEasy;
 use log::{debug, trace};
 use std::fmt::Display;
 use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
 use url::Url;
 
 type Result<T> = std::result::Result<T, Error>;
@@ -12,6 +14,41 @@ type Result<T> = std::result::Result<T, Error>;
 pub struct HttpRequestHandler {
   curl: CurlEasy,
   base_url: Url,
+  /// Headers sent on every request in addition to the per-request ones.
+  persistent_headers: Vec<Header>,
+  /// Headers received in the last response, as name and value pairs.
+  response_headers: Vec<(String, String)>,
+}
+
+/// Transport performing the HTTP requests of the REST API.
+///
+/// Implemented by the libcurl based [HttpRequestHandler](struct.HttpRequestHandler.html),
+/// other implementations can serve canned responses without a live server.
+pub trait Transport {
+  /// Set HTTP headers for the next requests, along with any persistent ones.
+  fn headers(&mut self, headers: &[Header]) -> Result<()>;
+
+  /// Get the headers received in the last response, as name and value pairs.
+  fn response_headers(&self) -> &[(String, String)];
+
+  /// Perform a GET request and return the status code with the response body.
+  fn get(&mut self, path_and_query: &str) -> Result<(u16, Vec<u8>)>;
+
+  /// Perform a PUT request and return the status code with the response body.
+  fn put(&mut self, path_and_query: &str, tx_data: Option<&[u8]>) -> Result<(u16, Vec<u8>)>;
+
+  /// Perform a POST request and return the status code with the response body.
+  fn post(&mut self, path_and_query: &str, tx_data: Option<&[u8]>) -> Result<(u16, Vec<u8>)>;
+
+  /// Perform a DELETE request and return the status code with the response body.
+  fn delete(&mut self, path_and_query: &str) -> Result<(u16, Vec<u8>)>;
+
+  /// Get the URL a request for `path_and_query` is sent to, used to report failed requests.
+  ///
+  /// Defaults to `path_and_query` itself.
+  fn url(&self, path_and_query: &str) -> String {
+    path_and_query.to_string()
+  }
 }
 
 /// HTTP Request Handler errors.
@@ -37,8 +74,16 @@ pub enum AuthMethod {
 pub enum Header {
   /// "Content-Type: application/json"
   ContentTypeAppJson,
+  /// "Content-Type: text/plain"
+  ContentTypeTextPlain,
+  /// "Content-Type: application/octet-stream"
+  ContentTypeOctetStream,
   /// "Accept: application/json"
   AcceptAppJson,
+  /// "Authorization: <credentials>"
+  Authorization(String),
+  /// "If-None-Match: <etag>"
+  IfNoneMatch(String),
   /// Any other header
   Custom(String),
 }
@@ -52,7 +97,12 @@ impl HttpRequestHandler {
     curl.password(password)?;
     curl.follow_location(true)?;
     curl.verbose(log::max_level() >= log::LevelFilter::Debug)?;
-    Ok(Self { curl, base_url })
+    Ok(Self {
+      curl,
+      base_url,
+      persistent_headers: Vec::new(),
+      response_headers: Vec::new(),
+    })
   }
 
   /// Specify the HTTP authentication method.
@@ -66,54 +116,95 @@ impl HttpRequestHandler {
     Ok(self)
   }
 
+  /// Authenticate with a bearer token instead of username and password.
+  ///
+  /// The token is sent as an `Authorization: Bearer` header on every request,
+  /// and the credentials given at creation are cleared so they are never sent.
+  pub fn bearer_auth(mut self, token: &str) -> Result<Self> {
+    self.curl.username("")?;
+    self.curl.password("")?;
+    self.curl.http_auth(&curl::easy::Auth::new())?;
+    self
+      .persistent_headers
+      .retain(|header| !matches!(header, Header::Authorization(_)));
+    self
+      .persistent_headers
+      .push(Header::Authorization(format!("Bearer {}", token)));
+    self.headers(&[])?;
+    Ok(self)
+  }
+
   /// Enable/Disable SSL verification of both host and peer.
-  pub fn ssl_verify(mut self, enable: bool) -> Result<Self> {
+  pub fn ssl_verify(self, enable: bool) -> Result<Self> {
+    self.ssl_verify_host(enable)?.ssl_verify_peer(enable)
+  }
+
+  /// Enable/Disable SSL verification that the certificate is for the server host name.
+  pub fn ssl_verify_host(mut self, enable: bool) -> Result<Self> {
     self.curl.ssl_verify_host(enable)?;
+    Ok(self)
+  }
+
+  /// Enable/Disable SSL verification of the authenticity of the peer certificate.
+  pub fn ssl_verify_peer(mut self, enable: bool) -> Result<Self> {
     self.curl.ssl_verify_peer(enable)?;
     Ok(self)
   }
 
-  /// Set HTTP headers.
-  pub fn headers(&mut self, in_headers: &[Header]) -> Result<&mut Self> {
-    let mut headers = curl::easy::List::new();
-    for header in in_headers {
-      headers.append(header.to_string().as_str())?;
+  /// Verify the peer certificate with the CA certificates in the given PEM file.
+  pub fn ca_cert(mut self, path: &Path) -> Result<Self> {
+    self.curl.cainfo(path)?;
+    Ok(self)
+  }
+
+  /// Verify the peer certificate with the given PEM encoded CA certificates.
+  pub fn ca_cert_blob(mut self, pem: &[u8]) -> Result<Self> {
+    self.curl.ssl_cainfo_blob(pem)?;
+    Ok(self)
+  }
+
+  /// Authenticate with a client certificate and its private key, given as PEM files,
+  /// and the password of the private key if it is encrypted.
+  pub fn client_cert(mut self, cert: &Path, key: &Path, key_password: Option<&str>) -> Result<Self> {
+    self.curl.ssl_cert(cert)?;
+    self.curl.ssl_key(key)?;
+    if let Some(key_password) = key_password {
+      self.curl.key_password(key_password)?;
     }
-    self.curl.http_headers(headers)?;
     Ok(self)
   }
 
-  /// Perform a GET request.
-  pub fn get(&mut self, path_and_query: &str) -> Result<(u32, Vec<u8>)> {
-    self.curl.get(true)?;
-    self.perform_request(path_and_query, None)
+  /// Set the maximum time a whole request is allowed to take, including connecting.
+  pub fn timeout(mut self, timeout: Duration) -> Result<Self> {
+    self.curl.timeout(timeout)?;
+    Ok(self)
   }
 
-  /// Perform a PUT request.
-  pub fn put(&mut self, path_and_query: &str, tx_data: Option<&[u8]>) -> Result<(u32, Vec<u8>)> {
-    self.curl.put(true)?;
-    self.perform_request(path_and_query, tx_data)
+  /// Set the maximum time allowed for connecting to the server.
+  pub fn connect_timeout(mut self, timeout: Duration) -> Result<Self> {
+    self.curl.connect_timeout(timeout)?;
+    Ok(self)
   }
 
-  /// Perform a POST request.
-  pub fn post(&mut self, path_and_query: &str, tx_data: Option<&[u8]>) -> Result<(u32, Vec<u8>)> {
-    self.curl.post(true)?;
-    self.perform_request(path_and_query, tx_data)
+  /// Enable/Disable TCP keep-alive probes.
+  pub fn tcp_keepalive(mut self, enable: bool) -> Result<Self> {
+    self.curl.tcp_keepalive(enable)?;
+    Ok(self)
   }
 
-  /// Perform a DELETE request.
-  pub fn delete(&mut self, path_and_query: &str) -> Result<(u32, Vec<u8>)> {
-    self.curl.custom_request("DELETE")?;
-    self.perform_request(path_and_query, None)
+  /// Set the time the connection must be idle before keep-alive probes are sent.
+  pub fn tcp_keepidle(mut self, idle: Duration) -> Result<Self> {
+    self.curl.tcp_keepidle(idle)?;
+    Ok(self)
   }
 
   /// Perform a generic HTTP Request and return the code with received response body.
-  fn perform_request(&mut self, path_and_query: &str, tx_data: Option<&[u8]>) -> Result<(u32, Vec<u8>)> {
+  fn perform_request(&mut self, path_and_query: &str, tx_data: Option<&[u8]>) -> Result<(u16, Vec<u8>)> {
     let url = self.base_url.join(path_and_query)?;
     self.curl.url(url.as_str())?;
     let rx_data = self.perform_transfer(tx_data)?;
     let code = self.curl.response_code()?;
-    Ok((code, rx_data))
+    Ok((code as u16, rx_data))
   }
 
   /// Perform CURL transfer and return the response body.
@@ -123,6 +214,8 @@ impl HttpRequestHandler {
     }
     let mut tx_data_mut = tx_data.unwrap_or(b"");
     let mut rx_data: Vec<u8> = Vec::new();
+    let rx_headers = &mut self.response_headers;
+    rx_headers.clear();
     {
       let mut transfer = self.curl.transfer();
       if tx_data.is_some() {
@@ -132,6 +225,16 @@ impl HttpRequestHandler {
         rx_data.extend_from_slice(new_data);
         Ok(new_data.len())
       })?;
+      transfer.header_function(|line| {
+        let line = String::from_utf8_lossy(line);
+        if line.starts_with("HTTP/") {
+          // Start of a new response, e.g. after following a redirect.
+          rx_headers.clear();
+        } else if let Some((name, value)) = line.split_once(':') {
+          rx_headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+        true
+      })?;
       transfer.debug_function(Self::curl_debug_function)?;
       transfer.perform()?;
     }
@@ -152,13 +255,67 @@ impl HttpRequestHandler {
   }
 }
 
+impl Transport for HttpRequestHandler {
+  fn headers(&mut self, in_headers: &[Header]) -> Result<()> {
+    let mut headers = curl::easy::List::new();
+    for header in self.persistent_headers.iter().chain(in_headers) {
+      headers.append(header.to_string().as_str())?;
+    }
+    self.curl.http_headers(headers)?;
+    Ok(())
+  }
+
+  fn response_headers(&self) -> &[(String, String)] {
+    &self.response_headers
+  }
+
+  // The handle is reused between requests, so each method sets the request method explicitly,
+  // otherwise a custom method of a previous request would stick.
+  // Request bodies are always sent as POST data, with the method name overridden when needed.
+
+  fn get(&mut self, path_and_query: &str) -> Result<(u16, Vec<u8>)> {
+    self.curl.get(true)?;
+    self.curl.custom_request("GET")?;
+    self.perform_request(path_and_query, None)
+  }
+
+  fn put(&mut self, path_and_query: &str, tx_data: Option<&[u8]>) -> Result<(u16, Vec<u8>)> {
+    self.curl.post(true)?;
+    self.curl.custom_request("PUT")?;
+    self.perform_request(path_and_query, Some(tx_data.unwrap_or_default()))
+  }
+
+  fn post(&mut self, path_and_query: &str, tx_data: Option<&[u8]>) -> Result<(u16, Vec<u8>)> {
+    self.curl.post(true)?;
+    self.curl.custom_request("POST")?;
+    self.perform_request(path_and_query, Some(tx_data.unwrap_or_default()))
+  }
+
+  fn delete(&mut self, path_and_query: &str) -> Result<(u16, Vec<u8>)> {
+    self.curl.get(true)?;
+    self.curl.custom_request("DELETE")?;
+    self.perform_request(path_and_query, None)
+  }
+
+  fn url(&self, path_and_query: &str) -> String {
+    match self.base_url.join(path_and_query) {
+      Ok(url) => url.into(),
+      Err(_) => path_and_query.to_string(),
+    }
+  }
+}
+
 impl Display for Header {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
-    f.write_str(match *self {
-      Header::ContentTypeAppJson => "Content-Type: application/json",
-      Header::AcceptAppJson => "Accept: application/json",
-      Header::Custom(ref s) => s.as_str(),
-    })
+    match *self {
+      Header::ContentTypeAppJson => f.write_str("Content-Type: application/json"),
+      Header::ContentTypeTextPlain => f.write_str("Content-Type: text/plain"),
+      Header::ContentTypeOctetStream => f.write_str("Content-Type: application/octet-stream"),
+      Header::AcceptAppJson => f.write_str("Accept: application/json"),
+      Header::Authorization(ref s) => write!(f, "Authorization: {}", s),
+      Header::IfNoneMatch(ref s) => write!(f, "If-None-Match: {}", s),
+      Header::Custom(ref s) => f.write_str(s),
+    }
   }
 }
 
@@ -171,6 +328,16 @@ impl Display for Error {
   }
 }
 
+impl Error {
+  /// Whether the request failed because a timeout was reached.
+  pub fn is_timeout(&self) -> bool {
+    match self {
+      Error::Curl(e) => e.is_operation_timedout(),
+      Error::Url(_) => false,
+    }
+  }
+}
+
 impl std::error::Error for Error {
   fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
     match *self {
@@ -191,3 +358,347 @@ impl From<url::ParseError> for Error {
     Error::Url(e)
   }
 }
+
+/// Transport serving canned responses, for tests.
+#[cfg(test)]
+pub(crate) mod mock {
+  use super::{Header, Result, Transport};
+  use std::collections::VecDeque;
+  use std::sync::{Arc, Mutex};
+
+  /// A change as returned by the get change endpoint, shared by the tests of the change endpoints.
+  pub const CHANGE: &str = r#"{
+    "id": "project~master~I8473b95934b5732ac55d26311a706c9c2bde9940",
+    "project": "project",
+    "branch": "master",
+    "change_id": "I8473b95934b5732ac55d26311a706c9c2bde9940",
+    "subject": "Implementing Feature X",
+    "status": "NEW",
+    "created": "2013-02-01 09:59:32.126000000",
+    "updated": "2013-02-21 11:16:36.775000000",
+    "insertions": 34,
+    "deletions": 101,
+    "_number": 3965,
+    "owner": { "_account_id": 1000096, "name": "John Doe" }
+  }"#;
+
+  /// A request performed through the MockTransport.
+  #[derive(Debug, Clone, PartialEq, Eq)]
+  pub struct MockRequest {
+    pub method: &'static str,
+    pub path: String,
+    pub headers: Vec<Header>,
+    pub body: Option<Vec<u8>>,
+  }
+
+  /// A canned response, as status code, headers and body.
+  type MockResponse = (u16, Vec<(String, String)>, Vec<u8>);
+
+  #[derive(Debug, Default)]
+  struct MockState {
+    responses: VecDeque<MockResponse>,
+    requests: Vec<MockRequest>,
+  }
+
+  /// Transport that answers requests with the queued responses in order, and records the requests.
+  ///
+  /// Clones share the responses and requests, so a clone kept by the test can inspect what was sent.
+  #[derive(Debug, Clone, Default)]
+  pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+    headers: Vec<Header>,
+    response_headers: Vec<(String, String)>,
+  }
+
+  impl MockTransport {
+    pub fn new() -> Self {
+      Self::default()
+    }
+
+    /// Queue a response with the given status code and body.
+    pub fn respond(self, code: u16, body: &str) -> Self {
+      self.respond_with_headers(code, &[], body)
+    }
+
+    /// Queue a JSON response, adding the magic prefix to the body.
+    pub fn respond_json(self, code: u16, json: &str) -> Self {
+      self.respond(code, &format!(")]}}'\n{}", json))
+    }
+
+    /// Queue a response with the given status code, headers and body.
+    pub fn respond_with_headers(self, code: u16, headers: &[(&str, &str)], body: &str) -> Self {
+      let headers = headers.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+      let response = (code, headers, body.as_bytes().to_vec());
+      self.state.lock().unwrap().responses.push_back(response);
+      self
+    }
+
+    /// Create a GerritRestApi performing its requests through a clone of this transport.
+    pub fn gerrit(&self) -> crate::GerritRestApi<MockTransport> {
+      crate::GerritRestApi::with_transport(self.clone())
+    }
+
+    /// Get the requests performed so far.
+    pub fn requests(&self) -> Vec<MockRequest> {
+      self.state.lock().unwrap().requests.clone()
+    }
+
+    fn perform(&mut self, method: &'static str, path: &str, body: Option<&[u8]>) -> Result<(u16, Vec<u8>)> {
+      let mut state = self.state.lock().unwrap();
+      state.requests.push(MockRequest {
+        method,
+        path: path.into(),
+        headers: self.headers.clone(),
+        body: body.map(<[u8]>::to_vec),
+      });
+      let (code, headers, body) = state
+        .responses
+        .pop_front()
+        .unwrap_or_else(|| panic!("no response queued for {} {}", method, path));
+      self.response_headers = headers;
+      Ok((code, body))
+    }
+  }
+
+  impl Transport for MockTransport {
+    fn headers(&mut self, headers: &[Header]) -> Result<()> {
+      self.headers = headers.to_vec();
+      Ok(())
+    }
+
+    fn response_headers(&self) -> &[(String, String)] {
+      &self.response_headers
+    }
+
+    fn get(&mut self, path_and_query: &str) -> Result<(u16, Vec<u8>)> {
+      self.perform("GET", path_and_query, None)
+    }
+
+    fn put(&mut self, path_and_query: &str, tx_data: Option<&[u8]>) -> Result<(u16, Vec<u8>)> {
+      self.perform("PUT", path_and_query, tx_data)
+    }
+
+    fn post(&mut self, path_and_query: &str, tx_data: Option<&[u8]>) -> Result<(u16, Vec<u8>)> {
+      self.perform("POST", path_and_query, tx_data)
+    }
+
+    fn delete(&mut self, path_and_query: &str) -> Result<(u16, Vec<u8>)> {
+      self.perform("DELETE", path_and_query, None)
+    }
+  }
+}
+
+/// Throwaway certificates and a single request HTTPS server, for TLS tests.
+#[cfg(test)]
+pub(crate) mod tls {
+  use openssl::asn1::Asn1Time;
+  use openssl::bn::BigNum;
+  use openssl::ec::{EcGroup, EcKey};
+  use openssl::hash::MessageDigest;
+  use openssl::nid::Nid;
+  use openssl::pkey::{PKey, Private};
+  use openssl::ssl::{SslAcceptor, SslMethod, SslVerifyMode};
+  use openssl::x509::extension::{BasicConstraints, SubjectAlternativeName};
+  use openssl::x509::{X509Name, X509};
+  use std::io::{BufRead, BufReader, Write};
+  use std::net::TcpListener;
+  use std::thread::JoinHandle;
+  use url::Url;
+
+  /// A certificate authority issuing certificates for tests.
+  pub struct TestCa {
+    pub cert: X509,
+    key: PKey<Private>,
+  }
+
+  /// A certificate with its private key.
+  pub struct Identity {
+    pub cert: X509,
+    pub key: PKey<Private>,
+  }
+
+  fn generate_key() -> PKey<Private> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+  }
+
+  fn build_cert(name: &str, key: &PKey<Private>, issuer: Option<&TestCa>) -> X509 {
+    let mut subject = X509Name::builder().unwrap();
+    subject.append_entry_by_nid(Nid::COMMONNAME, name).unwrap();
+    let subject = subject.build();
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    let serial = BigNum::from_u32(serial_number()).unwrap().to_asn1_integer().unwrap();
+    builder.set_serial_number(&serial).unwrap();
+    builder.set_subject_name(&subject).unwrap();
+    builder.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+    builder.set_not_after(&Asn1Time::days_from_now(1).unwrap()).unwrap();
+    builder.set_pubkey(key).unwrap();
+    match issuer {
+      Some(ca) => {
+        builder.set_issuer_name(ca.cert.subject_name()).unwrap();
+        let mut san = SubjectAlternativeName::new();
+        match name.parse::<std::net::IpAddr>() {
+          Ok(_) => san.ip(name),
+          Err(_) => san.dns(name),
+        };
+        let san = san.build(&builder.x509v3_context(Some(&ca.cert), None)).unwrap();
+        builder.append_extension(san).unwrap();
+        builder.sign(&ca.key, MessageDigest::sha256()).unwrap();
+      }
+      None => {
+        builder.set_issuer_name(&subject).unwrap();
+        builder
+          .append_extension(BasicConstraints::new().critical().ca().build().unwrap())
+          .unwrap();
+        builder.sign(key, MessageDigest::sha256()).unwrap();
+      }
+    }
+    builder.build()
+  }
+
+  fn serial_number() -> u32 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().subsec_nanos()
+  }
+
+  impl TestCa {
+    pub fn new() -> Self {
+      let key = generate_key();
+      let cert = build_cert("Test CA", &key, None);
+      Self { cert, key }
+    }
+
+    /// Issue a certificate for `name`, an IP address or DNS name, usable by servers and clients alike.
+    pub fn issue(&self, name: &str) -> Identity {
+      let key = generate_key();
+      let cert = build_cert(name, &key, Some(self));
+      Identity { cert, key }
+    }
+  }
+
+  /// Serve a single HTTPS request with `identity`, answering it with `body`, and requiring a client
+  /// certificate issued by `client_ca` if given.
+  ///
+  /// Returns the server url and a handle yielding whether the request was served,
+  /// which is not the case if either side rejected the handshake.
+  pub fn serve(identity: &Identity, client_ca: Option<&TestCa>, body: &str) -> (Url, JoinHandle<bool>) {
+    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+    acceptor.set_certificate(&identity.cert).unwrap();
+    acceptor.set_private_key(&identity.key).unwrap();
+    if let Some(ca) = client_ca {
+      acceptor.cert_store_mut().add_cert(ca.cert.clone()).unwrap();
+      acceptor.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+    }
+    let acceptor = acceptor.build();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = Url::parse(&format!("https://{}/", listener.local_addr().unwrap())).unwrap();
+    let response = format!(
+      "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+      body.len(),
+      body
+    );
+    let server = std::thread::spawn(move || {
+      let (stream, _) = listener.accept().unwrap();
+      let mut stream = match acceptor.accept(stream) {
+        Ok(stream) => stream,
+        Err(_) => return false,
+      };
+      let mut reader = BufReader::new(&mut stream);
+      loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+          Ok(n) if n > 0 && !line.trim_end().is_empty() => {}
+          Ok(n) if n > 0 => break,
+          _ => return false,
+        }
+      }
+      stream.write_all(response.as_bytes()).is_ok()
+    });
+    (url, server)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::{BufRead, BufReader, Write};
+  use std::net::TcpListener;
+  use std::thread::JoinHandle;
+
+  /// Serve a single request with an empty 200 response, returning the server url
+  /// and a handle yielding the request line and headers as received.
+  fn serve() -> (Url, JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+    let server = std::thread::spawn(move || {
+      let (stream, _) = listener.accept().unwrap();
+      let mut reader = BufReader::new(stream.try_clone().unwrap());
+      let mut lines = Vec::new();
+      loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        if line.trim_end().is_empty() {
+          break;
+        }
+        lines.push(line.trim_end().to_string());
+      }
+      (&stream)
+        .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+        .unwrap();
+      lines
+    });
+    (url, server)
+  }
+
+  fn authorization(request: &[String]) -> Vec<&str> {
+    request
+      .iter()
+      .filter_map(|line| line.split_once(':'))
+      .filter(|(name, _)| name.eq_ignore_ascii_case("authorization"))
+      .map(|(_, value)| value.trim())
+      .collect()
+  }
+
+  #[test]
+  fn bearer_auth() {
+    let (url, server) = serve();
+    let mut http = HttpRequestHandler::new(url, "jdoe", "secret")
+      .unwrap()
+      .bearer_auth("t0k3n")
+      .unwrap();
+    let (code, _) = http.get("a/config/server/version").unwrap();
+    assert_eq!(code, 200);
+    let request = server.join().unwrap();
+    assert_eq!(request[0], "GET /a/config/server/version HTTP/1.1");
+    assert_eq!(authorization(&request), vec!["Bearer t0k3n"]);
+  }
+
+  #[test]
+  fn bearer_auth_replaces_http_auth() {
+    for auth in &[AuthMethod::Basic, AuthMethod::Digest] {
+      let (url, server) = serve();
+      let mut http = HttpRequestHandler::new(url, "jdoe", "secret")
+        .unwrap()
+        .http_auth(auth)
+        .unwrap()
+        .bearer_auth("t0k3n")
+        .unwrap();
+      http.headers(&[Header::AcceptAppJson]).unwrap();
+      http.get("a/accounts/self").unwrap();
+      let request = server.join().unwrap();
+      assert_eq!(authorization(&request), vec!["Bearer t0k3n"], "{:?}", auth);
+      assert!(request.contains(&"Accept: application/json".to_string()));
+    }
+  }
+
+  #[test]
+  fn request_url_is_joined_with_base_url() {
+    let url = Url::parse("https://gerrit.example.com/r/").unwrap();
+    let handler = HttpRequestHandler::new(url, "", "").unwrap();
+    assert_eq!(
+      handler.url("a/changes/?q=status%3Aopen"),
+      "https://gerrit.example.com/r/a/changes/?q=status%3Aopen"
+    );
+  }
+}
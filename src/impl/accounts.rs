@@ -0,0 +1,421 @@
+//! Account Endpoint implementation.
+
+use crate::accounts::*;
+use crate::changes::ChangeInfo;
+use crate::handler::encode_path_segment;
+use crate::http::Transport;
+use crate::{GerritRestApi, Result};
+use ::http::StatusCode;
+use serde_derive::Serialize;
+
+/// Implement trait [AccountEndpoints](trait.AccountEndpoints.html) for Gerrit REST API.
+impl<T: Transport> AccountEndpoints for GerritRestApi<T> {
+  fn get_account(&mut self, account_id: &str) -> Result<AccountInfo> {
+    let json = self
+      .rest
+      .get(format!("a/accounts/{}", encode_path_segment(account_id)).as_str())?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let account = serde_json::from_str(&json)?;
+    Ok(account)
+  }
+
+  fn query_accounts(&mut self, params: &AccountQueryParams) -> Result<Vec<AccountInfo>> {
+    let json = self
+      .rest
+      .get_with_query("a/accounts/", params)?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let accounts = serde_json::from_str(&json)?;
+    Ok(accounts)
+  }
+
+  fn get_account_name(&mut self, account_id: &str) -> Result<String> {
+    let json = self
+      .rest
+      .get(format!("a/accounts/{}/name", encode_path_segment(account_id)).as_str())?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let name = serde_json::from_str(&json)?;
+    Ok(name)
+  }
+
+  fn set_account_name(&mut self, account_id: &str, input: &AccountNameInput) -> Result<Option<String>> {
+    let response = self.rest.put_json(
+      format!("a/accounts/{}/name", encode_path_segment(account_id)).as_str(),
+      input,
+    )?;
+    match response.code {
+      StatusCode::OK => Ok(Some(serde_json::from_str(&response.message.json()?)?)),
+      StatusCode::NO_CONTENT => Ok(None),
+      _ => Err(response.into_error()),
+    }
+  }
+
+  fn delete_account_name(&mut self, account_id: &str) -> Result<()> {
+    self
+      .rest
+      .delete(format!("a/accounts/{}/name", encode_path_segment(account_id)).as_str())?
+      .expect(StatusCode::NO_CONTENT)?;
+    Ok(())
+  }
+
+  fn get_account_status(&mut self, account_id: &str) -> Result<String> {
+    let json = self
+      .rest
+      .get(format!("a/accounts/{}/status", encode_path_segment(account_id)).as_str())?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let status = serde_json::from_str(&json)?;
+    Ok(status)
+  }
+
+  fn set_account_status(&mut self, account_id: &str, input: &StatusInput) -> Result<Option<String>> {
+    let response = self.rest.put_json(
+      format!("a/accounts/{}/status", encode_path_segment(account_id)).as_str(),
+      input,
+    )?;
+    match response.code {
+      StatusCode::OK => Ok(Some(serde_json::from_str(&response.message.json()?)?)),
+      StatusCode::NO_CONTENT => Ok(None),
+      _ => Err(response.into_error()),
+    }
+  }
+
+  fn get_active(&mut self, account_id: &str) -> Result<bool> {
+    let response = self
+      .rest
+      .get(format!("a/accounts/{}/active", encode_path_segment(account_id)).as_str())?;
+    match response.code {
+      StatusCode::OK => Ok(true),
+      StatusCode::NO_CONTENT => Ok(false),
+      _ => Err(response.into_error()),
+    }
+  }
+
+  fn set_active(&mut self, account_id: &str) -> Result<()> {
+    let response = self
+      .rest
+      .put(format!("a/accounts/{}/active", encode_path_segment(account_id)).as_str())?;
+    match response.code {
+      StatusCode::OK | StatusCode::CREATED => Ok(()),
+      _ => Err(response.into_error()),
+    }
+  }
+
+  fn delete_active(&mut self, account_id: &str) -> Result<()> {
+    self
+      .rest
+      .delete(format!("a/accounts/{}/active", encode_path_segment(account_id)).as_str())?
+      .expect(StatusCode::NO_CONTENT)?;
+    Ok(())
+  }
+
+  fn list_ssh_keys(&mut self, account_id: &str) -> Result<Vec<SshKeyInfo>> {
+    let json = self
+      .rest
+      .get(format!("a/accounts/{}/sshkeys", encode_path_segment(account_id)).as_str())?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let keys = serde_json::from_str(&json)?;
+    Ok(keys)
+  }
+
+  fn get_ssh_key(&mut self, account_id: &str, seq: u32) -> Result<SshKeyInfo> {
+    let json = self
+      .rest
+      .get(format!("a/accounts/{}/sshkeys/{}", encode_path_segment(account_id), seq).as_str())?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let key = serde_json::from_str(&json)?;
+    Ok(key)
+  }
+
+  fn add_ssh_key(&mut self, account_id: &str, key: &str) -> Result<SshKeyInfo> {
+    let json = self
+      .rest
+      .post_text(
+        format!("a/accounts/{}/sshkeys", encode_path_segment(account_id)).as_str(),
+        key,
+      )?
+      .expect(StatusCode::CREATED)?
+      .json()?;
+    let key = serde_json::from_str(&json)?;
+    Ok(key)
+  }
+
+  fn delete_ssh_key(&mut self, account_id: &str, seq: u32) -> Result<()> {
+    self
+      .rest
+      .delete(format!("a/accounts/{}/sshkeys/{}", encode_path_segment(account_id), seq).as_str())?
+      .expect(StatusCode::NO_CONTENT)?;
+    Ok(())
+  }
+
+  fn get_starred_changes(&mut self, account_id: &str) -> Result<Vec<ChangeInfo>> {
+    let json = self
+      .rest
+      .get(format!("a/accounts/{}/starred.changes", encode_path_segment(account_id)).as_str())?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let changes = serde_json::from_str(&json)?;
+    Ok(changes)
+  }
+
+  fn star_change(&mut self, account_id: &str, change_id: &str) -> Result<()> {
+    self
+      .rest
+      .put(
+        format!(
+          "a/accounts/{}/starred.changes/{}",
+          encode_path_segment(account_id),
+          change_id
+        )
+        .as_str(),
+      )?
+      .expect(StatusCode::NO_CONTENT)?;
+    Ok(())
+  }
+
+  fn unstar_change(&mut self, account_id: &str, change_id: &str) -> Result<()> {
+    self
+      .rest
+      .delete(
+        format!(
+          "a/accounts/{}/starred.changes/{}",
+          encode_path_segment(account_id),
+          change_id
+        )
+        .as_str(),
+      )?
+      .expect(StatusCode::NO_CONTENT)?;
+    Ok(())
+  }
+
+  fn get_star_labels(&mut self, account_id: &str, change_id: &str) -> Result<Vec<String>> {
+    let json = self
+      .rest
+      .get(
+        format!(
+          "a/accounts/{}/stars.changes/{}",
+          encode_path_segment(account_id),
+          change_id
+        )
+        .as_str(),
+      )?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let labels = serde_json::from_str(&json)?;
+    Ok(labels)
+  }
+
+  fn update_star_labels(&mut self, account_id: &str, change_id: &str, input: &StarsInput) -> Result<Vec<String>> {
+    let json = self
+      .rest
+      .post_json(
+        format!(
+          "a/accounts/{}/stars.changes/{}",
+          encode_path_segment(account_id),
+          change_id
+        )
+        .as_str(),
+        input,
+      )?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let labels = serde_json::from_str(&json)?;
+    Ok(labels)
+  }
+
+  fn get_account_capabilities(&mut self, account_id: &str, filter: &[&str]) -> Result<CapabilityInfo> {
+    let json = self
+      .rest
+      .get_with_query(
+        &format!("a/accounts/{}/capabilities", encode_path_segment(account_id)),
+        &CapabilityQueryParams { q: filter },
+      )?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let capabilities = serde_json::from_str(&json)?;
+    Ok(capabilities)
+  }
+
+  fn get_avatar(&mut self, account_id: &str, size: Option<u32>) -> Result<Vec<u8>> {
+    let image = self
+      .rest
+      .get_with_query(
+        &format!("a/accounts/{}/avatar", encode_path_segment(account_id)),
+        &AvatarQueryParams { s: size },
+      )?
+      .expect(StatusCode::OK)?
+      .raw();
+    Ok(image)
+  }
+
+  fn get_watched_projects(&mut self, account_id: &str) -> Result<Vec<ProjectWatchInfo>> {
+    let json = self
+      .rest
+      .get(format!("a/accounts/{}/watched.projects", encode_path_segment(account_id)).as_str())?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let watches = serde_json::from_str(&json)?;
+    Ok(watches)
+  }
+
+  fn add_watched_projects(&mut self, account_id: &str, watches: &[ProjectWatchInfo]) -> Result<Vec<ProjectWatchInfo>> {
+    let json = self
+      .rest
+      .post_json(
+        format!("a/accounts/{}/watched.projects", encode_path_segment(account_id)).as_str(),
+        watches,
+      )?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let watches = serde_json::from_str(&json)?;
+    Ok(watches)
+  }
+
+  fn delete_watched_projects(&mut self, account_id: &str, watches: &[ProjectWatchInfo]) -> Result<()> {
+    let watches: Vec<ProjectWatchKey> = watches
+      .iter()
+      .map(|watch| ProjectWatchKey {
+        project: &watch.project,
+        filter: watch.filter.as_deref(),
+      })
+      .collect();
+    self
+      .rest
+      .post_json(
+        format!("a/accounts/{}/watched.projects:delete", encode_path_segment(account_id)).as_str(),
+        &watches,
+      )?
+      .expect(StatusCode::NO_CONTENT)?;
+    Ok(())
+  }
+}
+
+/// Query parameters for filtering the capabilities of an account.
+#[derive(Serialize)]
+struct CapabilityQueryParams<'a> {
+  q: &'a [&'a str],
+}
+
+/// Query parameters for the size of an account avatar.
+#[derive(Serialize)]
+struct AvatarQueryParams {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  s: Option<u32>,
+}
+
+/// The project and filter identifying a project watch to delete.
+#[derive(Serialize)]
+struct ProjectWatchKey<'a> {
+  project: &'a str,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  filter: Option<&'a str>,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::http::mock::MockTransport;
+
+  #[test]
+  fn star_change_with_custom_label() {
+    let mock = MockTransport::new()
+      .respond(204, "")
+      .respond_json(200, r#"["blue", "red", "star"]"#)
+      .respond_json(200, r#"["blue", "star"]"#);
+    let mut gerrit = mock.gerrit();
+    gerrit.star_change("self", "3965").unwrap();
+    let input = StarsInput {
+      add: Some(vec!["blue".into(), "red".into()]),
+      remove: None,
+    };
+    let labels = gerrit.update_star_labels("self", "3965", &input).unwrap();
+    assert_eq!(labels, vec!["blue", "red", "star"]);
+    let input = StarsInput {
+      add: None,
+      remove: Some(vec!["red".into()]),
+    };
+    let labels = gerrit.update_star_labels("self", "3965", &input).unwrap();
+    assert_eq!(labels, vec!["blue", "star"]);
+    let requests = mock.requests();
+    assert_eq!(requests[0].method, "PUT");
+    assert_eq!(requests[0].path, "a/accounts/self/starred.changes/3965");
+    assert_eq!(requests[1].method, "POST");
+    assert_eq!(requests[1].path, "a/accounts/self/stars.changes/3965");
+    assert_eq!(requests[1].body.as_deref(), Some(&br#"{"add":["blue","red"]}"#[..]));
+    assert_eq!(requests[2].body.as_deref(), Some(&br#"{"remove":["red"]}"#[..]));
+  }
+
+  #[test]
+  fn unstar_change() {
+    let mock = MockTransport::new().respond(204, "");
+    mock.gerrit().unstar_change("self", "3965").unwrap();
+    let requests = mock.requests();
+    assert_eq!(requests[0].method, "DELETE");
+    assert_eq!(requests[0].path, "a/accounts/self/starred.changes/3965");
+  }
+
+  #[test]
+  fn account_endpoints_encode_account_id() {
+    let mock = MockTransport::new()
+      .respond_json(200, r#"{"_account_id": 1000097, "email": "jane.roe+ci@example.com"}"#)
+      .respond(200, "")
+      .respond(204, "");
+    let mut gerrit = mock.gerrit();
+    gerrit.get_account("jane.roe+ci@example.com").unwrap();
+    assert!(gerrit.get_active("jane.roe+ci@example.com").unwrap());
+    gerrit.unstar_change("jane.roe+ci@example.com", "3965").unwrap();
+    let paths: Vec<String> = mock.requests().into_iter().map(|r| r.path).collect();
+    assert_eq!(
+      paths,
+      vec![
+        "a/accounts/jane.roe%2Bci%40example.com",
+        "a/accounts/jane.roe%2Bci%40example.com/active",
+        "a/accounts/jane.roe%2Bci%40example.com/starred.changes/3965",
+      ]
+    );
+  }
+
+  #[test]
+  fn query_active_accounts() {
+    let mock = MockTransport::new().respond_json(
+      200,
+      r#"[
+        {
+          "_account_id": 1000096,
+          "name": "John Doe",
+          "email": "john.doe@example.com",
+          "username": "john"
+        },
+        {
+          "_account_id": 1000097,
+          "name": "Jane Roe",
+          "display_name": "Jane",
+          "email": "jane.roe@example.com",
+          "username": "jane",
+          "status": "OOO",
+          "_more_accounts": true
+        }
+      ]"#,
+    );
+    let params = AccountQueryParams {
+      search_query: Some("is:active".into()),
+      additional_opts: Some(vec![AccountOpt::Details]),
+      ..Default::default()
+    };
+    let accounts = mock.gerrit().query_accounts(&params).unwrap();
+    assert_eq!(mock.requests()[0].path, "a/accounts/?q=is%3Aactive&o=DETAILS");
+    assert_eq!(accounts.len(), 2);
+    assert_eq!(accounts[0].account_id, 1000096);
+    assert_eq!(accounts[0].name.as_deref(), Some("John Doe"));
+    assert_eq!(accounts[0].username.as_deref(), Some("john"));
+    assert!(!accounts[0].more_accounts);
+    assert_eq!(accounts[1].display_name.as_deref(), Some("Jane"));
+    assert_eq!(accounts[1].email.as_deref(), Some("jane.roe@example.com"));
+    assert_eq!(accounts[1].status.as_deref(), Some("OOO"));
+    assert!(accounts[1].more_accounts);
+  }
+}
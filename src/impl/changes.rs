@@ -2,14 +2,18 @@
 
 use crate::accounts::AccountInfo;
 use crate::changes::*;
+use crate::error::Error;
+use crate::handler::encode_path_segment;
+use crate::http::Transport;
 use crate::{GerritRestApi, Result};
 use ::http::StatusCode;
 use serde_derive::Serialize;
 use serde_with::skip_serializing_none;
 use std::collections::BTreeMap;
+use std::path::Path;
 
 /// Implement trait [ChangeEndpoints](trait.ChangeEndpoints.html) for Gerrit REST API.
-impl ChangeEndpoints for GerritRestApi {
+impl<T: Transport> ChangeEndpoints for GerritRestApi<T> {
   fn create_change(&mut self, change: &ChangeInput) -> Result<ChangeInfo> {
     let json = self
       .rest
@@ -21,36 +25,42 @@ impl ChangeEndpoints for GerritRestApi {
   }
 
   fn query_changes(&mut self, query: &QueryParams) -> Result<Vec<Vec<ChangeInfo>>> {
-    let params = serde_url_params::to_string(query)?;
-    let url = format!("a/changes/{}{}", if params.is_empty() { "" } else { "?" }, params);
-    let json = self.rest.get(&url)?.expect(StatusCode::OK)?.json()?;
-    let changes = if query.search_queries.is_some() && query.search_queries.as_ref().unwrap().len() > 1 {
-      serde_json::from_str::<Vec<Vec<ChangeInfo>>>(&json)?
-    } else {
-      vec![serde_json::from_str::<Vec<ChangeInfo>>(&json)?]
-    };
-    Ok(changes)
+    if query.search_queries.as_ref().map_or(true, Vec::is_empty) {
+      return Ok(Vec::new());
+    }
+    let json = self.rest.get(&query.url()?)?.expect(StatusCode::OK)?.json()?;
+    query.parse_changes(&json)
+  }
+
+  fn query_changes_single(&mut self, query: &QueryParams) -> Result<Vec<ChangeInfo>> {
+    let num_queries = query.search_queries.as_ref().map_or(0, Vec::len);
+    if num_queries > 1 {
+      return Err(Error::InvalidInput(format!(
+        "expected a single query, got {}; use query_changes for multiple queries",
+        num_queries
+      )));
+    }
+    self.rest.get(&query.url()?)?.expect_json(StatusCode::OK)
+  }
+
+  fn query_changes_paged(&mut self, query: &QueryParams) -> ChangeQueryIter<'_, Self> {
+    ChangeQueryIter::new(self, query)
   }
 
   fn get_change(&mut self, change_id: &str, additional_opts: Option<Vec<AdditionalOpt>>) -> Result<ChangeInfo> {
-    let query = QueryParams {
-      search_queries: None,
-      additional_opts,
-      limit: None,
-      start: None,
-    };
-    let params = serde_url_params::to_string(&query)?;
-    let url = format!(
-      "a/changes/{}/{}{}",
-      change_id,
-      if params.is_empty() { "" } else { "?" },
-      params
-    );
-    let json = self.rest.get(&url)?.expect(StatusCode::OK)?.json()?;
-    let change_info = serde_json::from_str(&json)?;
+    let change_info = serde_json::from_value(self.get_change_raw(change_id, additional_opts)?)?;
     Ok(change_info)
   }
 
+  fn get_change_raw(
+    &mut self, change_id: &str, additional_opts: Option<Vec<AdditionalOpt>>,
+  ) -> Result<serde_json::Value> {
+    self
+      .rest
+      .get(&change_url(change_id, additional_opts)?)?
+      .expect_json(StatusCode::OK)
+  }
+
   fn get_change_detail(&mut self, change_id: &str, additional_opts: Option<Vec<AdditionalOpt>>) -> Result<ChangeInfo> {
     let query = QueryParams {
       search_queries: None,
@@ -58,18 +68,68 @@ impl ChangeEndpoints for GerritRestApi {
       limit: None,
       start: None,
     };
-    let params = serde_url_params::to_string(&query)?;
-    let url = format!(
-      "a/changes/{}/detail/{}{}",
-      change_id,
-      if params.is_empty() { "" } else { "?" },
-      params
-    );
-    let json = self.rest.get(&url)?.expect(StatusCode::OK)?.json()?;
+    let json = self
+      .rest
+      .get_with_query(&format!("a/changes/{}/detail/", change_id), &query)?
+      .expect(StatusCode::OK)?
+      .json()?;
     let change_info = serde_json::from_str(&json)?;
     Ok(change_info)
   }
 
+  fn get_change_with_reviewer_history(&mut self, change_id: &str) -> Result<(ChangeInfo, Vec<ReviewerHistory>)> {
+    let change = self.get_change_detail(change_id, Some(vec![AdditionalOpt::ReviewerUpdates]))?;
+    let history = change.reviewer_history();
+    Ok((change, history))
+  }
+
+  fn get_change_with_etag(&mut self, change_id: &str) -> Result<(ChangeInfo, Option<String>)> {
+    let response = self
+      .rest
+      .get(format!("a/changes/{}", change_id).as_str())?
+      .expect_or(StatusCode::OK)?;
+    let etag = response.etag().map(String::from);
+    let change_info = serde_json::from_str(&response.message.json()?)?;
+    Ok((change_info, etag))
+  }
+
+  fn get_change_if_changed(&mut self, change_id: &str, etag: &str) -> Result<Option<ChangeInfo>> {
+    let response = self
+      .rest
+      .get_if_none_match(format!("a/changes/{}", change_id).as_str(), etag)?;
+    match response.code {
+      StatusCode::NOT_MODIFIED => Ok(None),
+      StatusCode::OK => Ok(Some(serde_json::from_str(&response.message.json()?)?)),
+      _ => Err(response.into_error()),
+    }
+  }
+
+  fn get_change_with_tracking_ids(&mut self, change_id: &str) -> Result<ChangeInfo> {
+    self.get_change(change_id, Some(vec![AdditionalOpt::TrackingIds]))
+  }
+
+  fn get_change_meta_diff(
+    &mut self, change_id: &str, old_meta: Option<&str>, new_meta: Option<&str>,
+  ) -> Result<ChangeInfoDifference> {
+    #[skip_serializing_none]
+    #[derive(Serialize)]
+    pub struct Query<'a> {
+      pub old: Option<&'a str>,
+      pub meta: Option<&'a str>,
+    }
+    let query = Query {
+      old: old_meta,
+      meta: new_meta,
+    };
+    let json = self
+      .rest
+      .get_with_query(&format!("a/changes/{}/meta_diff", change_id), &query)?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let difference = serde_json::from_str(&json)?;
+    Ok(difference)
+  }
+
   fn create_merge_patch_set(&mut self, change_id: &str, input: &MergePatchSetInput) -> Result<ChangeInfo> {
     let json = self
       .rest
@@ -173,14 +233,11 @@ impl ChangeEndpoints for GerritRestApi {
       pub option: Option<&'a str>,
     }
     let query = Query { option: commit };
-    let params = serde_url_params::to_string(&query)?;
-    let url = format!(
-      "a/changes/{}/pure_revert{}{}",
-      change_id,
-      if params.is_empty() { "" } else { "?" },
-      params
-    );
-    let json = self.rest.get(&url)?.expect(StatusCode::OK)?.json()?;
+    let json = self
+      .rest
+      .get_with_query(&format!("a/changes/{}/pure_revert", change_id), &query)?
+      .expect(StatusCode::OK)?
+      .json()?;
     let pure_revert = serde_json::from_str(&json)?;
     Ok(pure_revert)
   }
@@ -205,6 +262,32 @@ impl ChangeEndpoints for GerritRestApi {
     Ok(change_info)
   }
 
+  fn abandon_matching(&mut self, query: &QueryStr, abandon: &AbandonInput) -> Result<Vec<(u32, Result<ChangeInfo>)>> {
+    let query = QueryParams {
+      search_queries: Some(vec![query.clone()]),
+      ..Default::default()
+    };
+    let changes = self.query_changes_paged(&query).collect::<Result<Vec<_>>>()?;
+    let results = changes
+      .into_iter()
+      .map(|change| (change.number, self.abandon_change(&change.id, abandon)))
+      .collect();
+    Ok(results)
+  }
+
+  fn restore_matching(&mut self, query: &QueryStr, restore: &RestoreInput) -> Result<Vec<(u32, Result<ChangeInfo>)>> {
+    let query = QueryParams {
+      search_queries: Some(vec![query.clone()]),
+      ..Default::default()
+    };
+    let changes = self.query_changes_paged(&query).collect::<Result<Vec<_>>>()?;
+    let results = changes
+      .into_iter()
+      .map(|change| (change.number, self.restore_change(&change.id, restore)))
+      .collect();
+    Ok(results)
+  }
+
   fn rebase_change(&mut self, change_id: &str, rebase: &RebaseInput) -> Result<ChangeInfo> {
     let json = self
       .rest
@@ -255,6 +338,26 @@ impl ChangeEndpoints for GerritRestApi {
     Ok(change_info)
   }
 
+  fn submit_change_and_get_submission(&mut self, change_id: &str, submit: &SubmitInput) -> Result<String> {
+    self
+      .submit_change(change_id, submit)?
+      .submission_id
+      .ok_or_else(|| Error::MissingResponseField("submission_id".into()))
+  }
+
+  fn query_by_submission(&mut self, submission_id: &str) -> Result<Vec<ChangeInfo>> {
+    let query = QueryParams {
+      search_queries: Some(vec![QueryStr::Cooked(vec![QueryOpr::Search(SearchOpr::SubmissionId(
+        submission_id.into(),
+      ))])]),
+      additional_opts: None,
+      limit: None,
+      start: None,
+    };
+    let changes = self.query_changes(&query)?.into_iter().flatten().collect();
+    Ok(changes)
+  }
+
   fn changes_submitted_together(
     &mut self, change_id: &str, additional_opts: Option<&Vec<AdditionalOpt>>,
   ) -> Result<SubmittedTogetherInfo> {
@@ -294,7 +397,7 @@ impl ChangeEndpoints for GerritRestApi {
     Ok(())
   }
 
-  fn list_change_comments(&mut self, change_id: &str) -> Result<BTreeMap<String, CommentInfo>> {
+  fn list_change_comments(&mut self, change_id: &str) -> Result<BTreeMap<String, Vec<CommentInfo>>> {
     let json = self
       .rest
       .get(format!("a/changes/{}/comments", change_id).as_str())?
@@ -304,7 +407,17 @@ impl ChangeEndpoints for GerritRestApi {
     Ok(comments)
   }
 
-  fn list_change_robot_comments(&mut self, change_id: &str) -> Result<BTreeMap<String, RobotCommentInfo>> {
+  fn get_all_comments(&mut self, change_id: &str) -> Result<Vec<(String, CommentInfo)>> {
+    let mut comments: Vec<(String, CommentInfo)> = self
+      .list_change_comments(change_id)?
+      .into_iter()
+      .flat_map(|(path, comments)| comments.into_iter().map(move |comment| (path.clone(), comment)))
+      .collect();
+    comments.sort_by_key(|(_, comment)| (comment.patch_set, comment.updated.0));
+    Ok(comments)
+  }
+
+  fn list_change_robot_comments(&mut self, change_id: &str) -> Result<BTreeMap<String, Vec<RobotCommentInfo>>> {
     let json = self
       .rest
       .get(format!("a/changes/{}/robotcomments", change_id).as_str())?
@@ -314,7 +427,7 @@ impl ChangeEndpoints for GerritRestApi {
     Ok(robot_comments)
   }
 
-  fn list_change_drafts(&mut self, change_id: &str) -> Result<BTreeMap<String, CommentInfo>> {
+  fn list_change_drafts(&mut self, change_id: &str) -> Result<BTreeMap<String, Vec<CommentInfo>>> {
     let json = self
       .rest
       .get(format!("a/changes/{}/drafts", change_id).as_str())?
@@ -324,6 +437,19 @@ impl ChangeEndpoints for GerritRestApi {
     Ok(drafts)
   }
 
+  fn delete_all_drafts(&mut self, change_id: &str) -> Result<usize> {
+    let drafts = self.list_change_drafts(change_id)?;
+    let mut deleted = 0;
+    for draft in drafts.values().flatten() {
+      let patch_set = draft
+        .patch_set
+        .ok_or_else(|| Error::MissingResponseField("patch_set".into()))?;
+      self.delete_draft(change_id, &patch_set.to_string(), &draft.id)?;
+      deleted += 1;
+    }
+    Ok(deleted)
+  }
+
   fn check_change(&mut self, change_id: &str) -> Result<ChangeInfo> {
     let json = self
       .rest
@@ -334,12 +460,15 @@ impl ChangeEndpoints for GerritRestApi {
     Ok(changes)
   }
 
-  fn fix_change(&mut self, change_id: &str) -> Result<ChangeInfo> {
-    let json = self
-      .rest
-      .post(format!("a/changes/{}/check", change_id).as_str())?
-      .expect(StatusCode::OK)?
-      .json()?;
+  fn fix_change(&mut self, change_id: &str, input: Option<&FixInput>) -> Result<ChangeInfo> {
+    let url = format!("a/changes/{}/check", change_id);
+    let json = if let Some(input) = input {
+      self.rest.post_json(&url, input)?
+    } else {
+      self.rest.post(&url)?
+    }
+    .expect(StatusCode::OK)?
+    .json()?;
     let changes = serde_json::from_str(&json)?;
     Ok(changes)
   }
@@ -433,9 +562,10 @@ impl ChangeEndpoints for GerritRestApi {
   }
 
   fn set_hashtags(&mut self, change_id: &str, input: &HashtagsInput) -> Result<Vec<String>> {
+    let input = input.normalized()?;
     let json = self
       .rest
-      .post_json(format!("a/changes/{}/hashtags", change_id).as_str(), input)?
+      .post_json(format!("a/changes/{}/hashtags", change_id).as_str(), &input)?
       .expect(StatusCode::OK)?
       .json()?;
     let hashtags = serde_json::from_str(&json)?;
@@ -485,6 +615,81 @@ impl ChangeEndpoints for GerritRestApi {
     Ok(message)
   }
 
+  fn get_edit_message(&mut self, change_id: &str) -> Result<String> {
+    let json = self
+      .rest
+      .get(format!("a/changes/{}/edit:message", change_id).as_str())?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let encoded: String = serde_json::from_str(&json)?;
+    let message = base64::decode(&encoded)?;
+    Ok(String::from_utf8(message)?)
+  }
+
+  fn set_edit_message(&mut self, change_id: &str, input: &ChangeEditMessageInput) -> Result<()> {
+    self
+      .rest
+      .put_json(format!("a/changes/{}/edit:message", change_id).as_str(), input)?
+      .expect(StatusCode::NO_CONTENT)?;
+    Ok(())
+  }
+
+  fn get_change_edit(&mut self, change_id: &str, list_files: bool) -> Result<Option<EditInfo>> {
+    let url = format!("a/changes/{}/edit{}", change_id, if list_files { "?list" } else { "" });
+    let response = self.rest.get(&url)?;
+    match response.code {
+      StatusCode::OK => Ok(Some(serde_json::from_str(&response.message.json()?)?)),
+      StatusCode::NO_CONTENT => Ok(None),
+      _ => Err(response.into_error()),
+    }
+  }
+
+  fn put_edit_file(&mut self, change_id: &str, file_path: &str, content: &[u8]) -> Result<()> {
+    self
+      .rest
+      .put_raw(
+        format!("a/changes/{}/edit/{}", change_id, encode_path_segment(file_path)).as_str(),
+        content,
+      )?
+      .expect(StatusCode::NO_CONTENT)?;
+    Ok(())
+  }
+
+  fn delete_edit_file(&mut self, change_id: &str, file_path: &str) -> Result<()> {
+    self
+      .rest
+      .delete(format!("a/changes/{}/edit/{}", change_id, encode_path_segment(file_path)).as_str())?
+      .expect(StatusCode::NO_CONTENT)?;
+    Ok(())
+  }
+
+  fn publish_change_edit(&mut self, change_id: &str, input: Option<&PublishChangeEditInput>) -> Result<()> {
+    let url = format!("a/changes/{}/edit:publish", change_id);
+    if let Some(input) = input {
+      self.rest.post_json(&url, input)?
+    } else {
+      self.rest.post(&url)?
+    }
+    .expect(StatusCode::NO_CONTENT)?;
+    Ok(())
+  }
+
+  fn rebase_change_edit(&mut self, change_id: &str) -> Result<()> {
+    self
+      .rest
+      .post(format!("a/changes/{}/edit:rebase", change_id).as_str())?
+      .expect(StatusCode::NO_CONTENT)?;
+    Ok(())
+  }
+
+  fn delete_change_edit(&mut self, change_id: &str) -> Result<()> {
+    self
+      .rest
+      .delete(format!("a/changes/{}/edit", change_id).as_str())?
+      .expect(StatusCode::NO_CONTENT)?;
+    Ok(())
+  }
+
   fn list_reviewers(&mut self, change_id: &str) -> Result<Vec<ReviewerInfo>> {
     let json = self
       .rest
@@ -496,34 +701,13 @@ impl ChangeEndpoints for GerritRestApi {
   }
 
   fn suggest_reviewers(
-    &mut self, change_id: &str, query_str: &str, limit: Option<u32>, exclude_groups: bool, cc: bool,
+    &mut self, change_id: &str, params: &SuggestReviewersParams,
   ) -> Result<Vec<SuggestedReviewerInfo>> {
-    #[skip_serializing_none]
-    #[derive(Serialize)]
-    pub struct Query<'a> {
-      #[serde(rename = "q")]
-      pub query_str: &'a str,
-      #[serde(rename = "n")]
-      pub limit: Option<u32>,
-      #[serde(rename = "exclude-groups")]
-      pub exclude_groups: Option<()>,
-      #[serde(rename = "reviewer-state")]
-      pub reviewer_state: Option<&'static str>,
-    }
-    let query = Query {
-      query_str,
-      limit,
-      exclude_groups: if exclude_groups { Some(()) } else { None },
-      reviewer_state: if cc { Some("CC") } else { None },
-    };
-    let params = serde_url_params::to_string(&query)?;
-    let url = format!(
-      "a/changes/{}/suggest_reviewers{}{}",
-      change_id,
-      if params.is_empty() { "" } else { "?" },
-      params
-    );
-    let json = self.rest.get(&url)?.expect(StatusCode::OK)?.json()?;
+    let json = self
+      .rest
+      .get_with_query(&format!("a/changes/{}/suggest_reviewers", change_id), params)?
+      .expect(StatusCode::OK)?
+      .json()?;
     let reviewers = serde_json::from_str(&json)?;
     Ok(reviewers)
   }
@@ -598,16 +782,14 @@ impl ChangeEndpoints for GerritRestApi {
     let query = Query {
       links: if links { Some(()) } else { None },
     };
-    let params = serde_url_params::to_string(&query)?;
-    let url = format!(
-      "a/changes/{}/revisions/{}/commit{}{}",
-      change_id,
-      revision_id,
-      if params.is_empty() { "" } else { "?" },
-      params
-    );
-
-    let json = self.rest.get(&url)?.expect(StatusCode::OK)?.json()?;
+    let json = self
+      .rest
+      .get_with_query(
+        &format!("a/changes/{}/revisions/{}/commit", change_id, revision_id),
+        &query,
+      )?
+      .expect(StatusCode::OK)?
+      .json()?;
     let commit = serde_json::from_str(&json)?;
     Ok(commit)
   }
@@ -645,6 +827,26 @@ impl ChangeEndpoints for GerritRestApi {
     Ok(commits)
   }
 
+  fn get_mergeable(&mut self, change_id: &str, revision_id: &str, other_branches: bool) -> Result<MergeableInfo> {
+    let url = format!(
+      "a/changes/{}/revisions/{}/mergeable{}",
+      change_id,
+      revision_id,
+      if other_branches { "?other-branches" } else { "" }
+    );
+    let json = self.rest.get(&url)?.expect(StatusCode::OK)?.json()?;
+    let mergeable = serde_json::from_str(&json)?;
+    Ok(mergeable)
+  }
+
+  fn get_or_compute_mergeable(&mut self, change_id: &str) -> Result<bool> {
+    if let Some(mergeable) = self.get_change(change_id, None)?.mergeable {
+      return Ok(mergeable);
+    }
+    let mergeable = self.get_mergeable(change_id, "current", false)?;
+    Ok(mergeable.mergeable)
+  }
+
   fn get_revision_actions(&mut self, change_id: &str, revision_id: &str) -> Result<BTreeMap<String, ActionInfo>> {
     let json = self
       .rest
@@ -666,16 +868,22 @@ impl ChangeEndpoints for GerritRestApi {
   }
 
   fn set_review(&mut self, change_id: &str, revision_id: &str, input: &ReviewInput) -> Result<ReviewResult> {
-    let json = self
+    input.validate()?;
+    let tagged;
+    let input = match &self.default_tag {
+      Some(tag) if input.tag.is_none() => {
+        tagged = ReviewInput {
+          tag: Some(tag.clone()),
+          ..input.clone()
+        };
+        &tagged
+      }
+      _ => input,
+    };
+    self
       .rest
-      .post_json(
-        format!("a/changes/{}/revisions/{}/review", change_id, revision_id).as_str(),
-        input,
-      )?
-      .expect(StatusCode::OK)?
-      .json()?;
-    let result = serde_json::from_str(&json)?;
-    Ok(result)
+      .post_json(&review_url(change_id, revision_id), input)?
+      .expect_json(StatusCode::OK)
   }
 
   fn get_related_changes(&mut self, change_id: &str, revision_id: &str) -> Result<RelatedChangesInfo> {
@@ -711,74 +919,1010 @@ impl ChangeEndpoints for GerritRestApi {
     Ok(submit)
   }
 
+  fn cherry_pick(&mut self, change_id: &str, revision_id: &str, input: &CherryPickInput) -> Result<ChangeInfo> {
+    let json = self
+      .rest
+      .post_json(
+        format!("a/changes/{}/revisions/{}/cherrypick", change_id, revision_id).as_str(),
+        input,
+      )?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let change_info = serde_json::from_str(&json)?;
+    Ok(change_info)
+  }
+
   fn get_patch(&mut self, change_id: &str, revision_id: &str, opts: &Option<PatchParams>) -> Result<Vec<u8>> {
-    let params = if let Some(opts) = opts {
-      serde_url_params::to_string(opts)?
-    } else {
-      String::default()
-    };
+    let patch = self
+      .rest
+      .get_with_query(
+        &format!("a/changes/{}/revisions/{}/patch", change_id, revision_id),
+        opts,
+      )?
+      .expect(StatusCode::OK)?
+      .raw();
+    let zipped = opts.as_ref().and_then(|opts| opts.zip.as_ref()).is_some();
+    if zipped {
+      return Ok(patch);
+    }
+    decode_base64(patch)
+  }
+
+  fn download_patch(
+    &mut self, change_id: &str, revision_id: &str, opts: &Option<PatchParams>, path: &Path,
+  ) -> Result<usize> {
+    let patch = self.get_patch(change_id, revision_id, opts)?;
+    std::fs::write(path, &patch)?;
+    Ok(patch.len())
+  }
+
+  fn submit_preview(&mut self, change_id: &str, revision_id: &str, format: CompressFormat) -> Result<Vec<u8>> {
     let url = format!(
-      "a/changes/{}/revisions/{}/patch{}{}",
+      "a/changes/{}/revisions/{}/preview_submit?format={}",
       change_id,
       revision_id,
-      if params.is_empty() { "" } else { "?" },
-      params
+      format.to_string().to_lowercase()
     );
-    let patch = self.rest.get(&url)?.expect(StatusCode::OK)?.raw();
-    Ok(patch)
-  }
-
-  fn submit_preview(&mut self, change_id: &str, revision_id: &str, format: CompressFormat) -> Result<Vec<u8>> {
-    todo!()
+    Ok(self.rest.get(&url)?.expect(StatusCode::OK)?.raw())
   }
 
-  fn list_drafts(&mut self, change_id: &str, revision_id: &str) -> Result<BTreeMap<String, CommentInfo>> {
-    todo!()
+  fn list_drafts(&mut self, change_id: &str, revision_id: &str) -> Result<BTreeMap<String, Vec<CommentInfo>>> {
+    self
+      .rest
+      .get(format!("a/changes/{}/revisions/{}/drafts/", change_id, revision_id).as_str())?
+      .expect_json(StatusCode::OK)
   }
 
   fn create_draft(&mut self, change_id: &str, revision_id: &str, input: &CommentInput) -> Result<CommentInfo> {
-    todo!()
+    self
+      .rest
+      .put_json(
+        format!("a/changes/{}/revisions/{}/drafts", change_id, revision_id).as_str(),
+        input,
+      )?
+      .expect_json(StatusCode::CREATED)
   }
 
   fn get_draft(&mut self, change_id: &str, revision_id: &str, draft_id: &str) -> Result<CommentInfo> {
-    todo!()
+    self
+      .rest
+      .get(format!("a/changes/{}/revisions/{}/drafts/{}", change_id, revision_id, draft_id).as_str())?
+      .expect_json(StatusCode::OK)
   }
 
   fn update_draft(&mut self, change_id: &str, revision_id: &str, input: &CommentInput) -> Result<CommentInfo> {
-    todo!()
+    let draft_id = input
+      .id
+      .as_deref()
+      .ok_or_else(|| Error::InvalidInput("the id of the draft to update must be set".into()))?;
+    self
+      .rest
+      .put_json(
+        format!("a/changes/{}/revisions/{}/drafts/{}", change_id, revision_id, draft_id).as_str(),
+        input,
+      )?
+      .expect_json(StatusCode::OK)
   }
 
   fn delete_draft(&mut self, change_id: &str, revision_id: &str, draft_id: &str) -> Result<()> {
-    todo!()
+    self
+      .rest
+      .delete(format!("a/changes/{}/revisions/{}/drafts/{}", change_id, revision_id, draft_id).as_str())?
+      .expect(StatusCode::NO_CONTENT)?;
+    Ok(())
   }
 
   fn list_comments(&mut self, change_id: &str, revision_id: &str) -> Result<BTreeMap<String, Vec<CommentInfo>>> {
-    todo!()
+    self
+      .rest
+      .get(format!("a/changes/{}/revisions/{}/comments/", change_id, revision_id).as_str())?
+      .expect_json(StatusCode::OK)
   }
 
   fn get_comment(&mut self, change_id: &str, revision_id: &str, comment_id: &str) -> Result<CommentInfo> {
-    todo!()
+    self
+      .rest
+      .get(
+        format!(
+          "a/changes/{}/revisions/{}/comments/{}",
+          change_id, revision_id, comment_id
+        )
+        .as_str(),
+      )?
+      .expect_json(StatusCode::OK)
   }
 
   fn delete_comment(&mut self, change_id: &str, revision_id: &str, comment_id: &str) -> Result<CommentInfo> {
-    todo!()
+    self
+      .rest
+      .post_json(
+        format!(
+          "a/changes/{}/revisions/{}/comments/{}/delete",
+          change_id, revision_id, comment_id
+        )
+        .as_str(),
+        &DeleteCommentInput { reason: None },
+      )?
+      .expect_json(StatusCode::OK)
   }
 
   fn list_files(
     &mut self, change_id: &str, revision_id: &str, opts: &Option<ListFilesParams>,
   ) -> Result<BTreeMap<String, FileInfo>> {
-    todo!()
+    if let Some(opts) = opts {
+      if opts.returns_paths()? {
+        return Err(Error::InvalidInput(
+          "the reviewed and q options return paths, use list_file_paths".into(),
+        ));
+      }
+    }
+    let json = self
+      .rest
+      .get_with_query(
+        &format!("a/changes/{}/revisions/{}/files/", change_id, revision_id),
+        opts,
+      )?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let files = serde_json::from_str(&json)?;
+    Ok(files)
+  }
+
+  fn list_file_paths(&mut self, change_id: &str, revision_id: &str, opts: &ListFilesParams) -> Result<Vec<String>> {
+    if !opts.returns_paths()? {
+      return Err(Error::InvalidInput(
+        "either the reviewed or the q option must be set".into(),
+      ));
+    }
+    let json = self
+      .rest
+      .get_with_query(
+        &format!("a/changes/{}/revisions/{}/files/", change_id, revision_id),
+        opts,
+      )?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let paths = serde_json::from_str(&json)?;
+    Ok(paths)
   }
 
   fn get_content(
     &mut self, change_id: &str, revision_id: &str, file_id: &str, opts: &Option<GetContentParams>,
   ) -> Result<Vec<u8>> {
-    todo!()
+    let content = self
+      .rest
+      .get_with_query(
+        &format!(
+          "a/changes/{}/revisions/{}/files/{}/content",
+          change_id, revision_id, file_id
+        ),
+        opts,
+      )?
+      .expect(StatusCode::OK)?
+      .raw();
+    decode_base64(content)
+  }
+
+  fn get_files_content(
+    &mut self, change_id: &str, revision_id: &str, paths: &[&str],
+  ) -> Result<BTreeMap<String, Vec<u8>>> {
+    let mut contents = BTreeMap::new();
+    for path in paths {
+      match self.get_content(change_id, revision_id, &encode_path_segment(path), &None) {
+        Ok(content) => {
+          contents.insert(path.to_string(), content);
+        }
+        Err(e) if e.is_not_found() => {}
+        Err(e) => return Err(e),
+      }
+    }
+    Ok(contents)
   }
 
   fn get_diff(
     &mut self, change_id: &str, revision_id: &str, file_id: &str, opts: &Option<DiffParams>,
   ) -> Result<DiffInfo> {
-    todo!()
+    let json = self
+      .rest
+      .get_with_query(
+        &format!(
+          "a/changes/{}/revisions/{}/files/{}/diff",
+          change_id, revision_id, file_id
+        ),
+        opts,
+      )?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let diff = serde_json::from_str(&json)?;
+    Ok(diff)
+  }
+}
+
+/// Decode a base64 response body, which may be wrapped in multiple lines.
+fn decode_base64(data: Vec<u8>) -> Result<Vec<u8>> {
+  let encoded: Vec<u8> = data.into_iter().filter(|b| !b.is_ascii_whitespace()).collect();
+  let decoded = base64::decode(&encoded)?;
+  Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::http::mock::{MockTransport, CHANGE};
+  use crate::http::Header;
+
+  fn queries(queries: &[&str]) -> QueryParams {
+    QueryParams {
+      search_queries: Some(queries.iter().map(|q| QueryStr::Raw(q.to_string())).collect()),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn query_changes_without_queries() {
+    let mock = MockTransport::new();
+    let mut gerrit = mock.gerrit();
+    assert!(gerrit.query_changes(&QueryParams::default()).unwrap().is_empty());
+    assert!(gerrit.query_changes(&queries(&[])).unwrap().is_empty());
+    assert!(mock.requests().is_empty());
+  }
+
+  #[test]
+  fn query_changes_with_one_query() {
+    let mock = MockTransport::new().respond_json(200, &format!("[{}]", CHANGE));
+    let changes = mock.gerrit().query_changes(&queries(&["status:open"])).unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].len(), 1);
+    assert_eq!(changes[0][0].number, 3965);
+    assert_eq!(mock.requests()[0].path, "a/changes/?q=status%3Aopen");
+  }
+
+  #[test]
+  fn query_changes_with_three_queries() {
+    let mock = MockTransport::new().respond_json(200, &format!("[[{0}], [], [{0}, {0}]]", CHANGE));
+    let query = queries(&["is:open", "is:merged", "owner:self"]);
+    let changes = mock.gerrit().query_changes(&query).unwrap();
+    assert_eq!(changes.iter().map(Vec::len).collect::<Vec<_>>(), vec![1, 0, 2]);
+    assert_eq!(
+      mock.requests()[0].path,
+      "a/changes/?q=is%3Aopen&q=is%3Amerged&q=owner%3Aself"
+    );
+  }
+
+  #[test]
+  fn query_changes_with_missing_results() {
+    let mock = MockTransport::new().respond_json(200, &format!("[[{}]]", CHANGE));
+    let query = queries(&["is:open", "is:merged"]);
+    assert!(mock.gerrit().query_changes(&query).is_err());
+  }
+
+  #[test]
+  fn query_changes_single_without_query() {
+    let mock = MockTransport::new().respond_json(200, &format!("[{}]", CHANGE));
+    let changes = mock.gerrit().query_changes_single(&QueryParams::default()).unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(mock.requests()[0].path, "a/changes/");
+  }
+
+  #[test]
+  fn query_changes_paged() {
+    let change = |number: u32, more_changes: bool| {
+      let mut change: serde_json::Value = serde_json::from_str(CHANGE).unwrap();
+      change["_number"] = number.into();
+      if more_changes {
+        change["_more_changes"] = true.into();
+      }
+      change
+    };
+    let page1 = serde_json::json!([change(1, false), change(2, true)]);
+    let page2 = serde_json::json!([change(3, false)]);
+    let mock = MockTransport::new()
+      .respond_json(200, &page1.to_string())
+      .respond_json(200, &page2.to_string());
+    let query = QueryParams {
+      limit: Some(2),
+      start: Some(10),
+      ..queries(&["status:open"])
+    };
+    let mut gerrit = mock.gerrit();
+    let numbers: Vec<u32> = gerrit
+      .query_changes_paged(&query)
+      .map(|change| change.unwrap().number)
+      .collect();
+    assert_eq!(numbers, vec![1, 2, 3]);
+    let paths: Vec<String> = mock.requests().into_iter().map(|r| r.path).collect();
+    assert_eq!(
+      paths,
+      vec![
+        "a/changes/?q=status%3Aopen&n=2&S=10",
+        "a/changes/?q=status%3Aopen&n=2&S=12"
+      ]
+    );
+  }
+
+  #[test]
+  fn get_change() {
+    let mock = MockTransport::new().respond_json(200, CHANGE);
+    let change = mock
+      .gerrit()
+      .get_change("3965", Some(vec![AdditionalOpt::CurrentRevision]))
+      .unwrap();
+    assert_eq!(change.number, 3965);
+    assert_eq!(change.subject, "Implementing Feature X");
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "GET");
+    assert_eq!(requests[0].path, "a/changes/3965/?o=CURRENT_REVISION");
+    assert_eq!(requests[0].headers, vec![Header::AcceptAppJson]);
+  }
+
+  #[test]
+  fn get_change_not_found() {
+    let mock = MockTransport::new().respond(404, "Not found: 1\n");
+    let error = mock.gerrit().get_change("1", None).unwrap_err();
+    assert!(error.is_not_found());
+  }
+
+  #[test]
+  fn get_change_if_changed() {
+    let mock = MockTransport::new()
+      .respond_with_headers(200, &[("ETag", "\"abc123\"")], &format!(")]}}'\n{}", CHANGE))
+      .respond(304, "")
+      .respond_with_headers(200, &[("ETag", "\"def456\"")], &format!(")]}}'\n{}", CHANGE));
+    let mut gerrit = mock.gerrit();
+    let (change, etag) = gerrit.get_change_with_etag("3965").unwrap();
+    assert_eq!(change.number, 3965);
+    assert_eq!(etag.as_deref(), Some("\"abc123\""));
+    let etag = etag.unwrap();
+    assert!(gerrit.get_change_if_changed("3965", &etag).unwrap().is_none());
+    let change = gerrit.get_change_if_changed("3965", &etag).unwrap();
+    assert_eq!(change.unwrap().number, 3965);
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 3);
+    for request in &requests[1..] {
+      assert_eq!(request.method, "GET");
+      assert_eq!(request.path, "a/changes/3965");
+      assert_eq!(
+        request.headers,
+        vec![Header::AcceptAppJson, Header::IfNoneMatch("\"abc123\"".into())]
+      );
+    }
+  }
+
+  fn review() -> ReviewInput {
+    let comment = CommentInput {
+      id: None,
+      path: None,
+      side: None,
+      line: Some(23),
+      range: None,
+      in_reply_to: None,
+      updated: None,
+      message: Some("[nit] trailing whitespace".into()),
+      tag: None,
+      unresolved: None,
+    };
+    ReviewInputBuilder::new()
+      .message("Some nits need to be fixed.")
+      .label("Code-Review", -1)
+      .comment(
+        "gerrit-server/src/main/java/com/google/gerrit/server/project/RefControl.java",
+        comment,
+      )
+      .build()
+  }
+
+  #[test]
+  fn set_review_applies_default_tag() {
+    let mock = MockTransport::new().respond_json(200, r#"{ "labels": { "Code-Review": -1 } }"#);
+    let mut gerrit = mock.gerrit();
+    gerrit.default_tag(Some("autogenerated:ci".into()));
+    let result = gerrit.set_review("3965", "current", &review()).unwrap();
+    assert_eq!(result.labels.unwrap()["Code-Review"], -1);
+    let requests = mock.requests();
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].path, "a/changes/3965/revisions/current/review");
+    let body: serde_json::Value = serde_json::from_slice(requests[0].body.as_ref().unwrap()).unwrap();
+    assert_eq!(body["tag"], "autogenerated:ci");
+    // Inline comments inherit the tag of the review on the server.
+    let comments = body["comments"].as_object().unwrap().values().next().unwrap();
+    assert!(comments[0].get("tag").is_none());
+  }
+
+  #[test]
+  fn set_review_explicit_tag_overrides_default_tag() {
+    let mock = MockTransport::new().respond_json(200, "{}");
+    let mut gerrit = mock.gerrit();
+    gerrit.default_tag(Some("autogenerated:ci".into()));
+    let input = ReviewInput {
+      tag: Some("autogenerated:lint".into()),
+      ..review()
+    };
+    gerrit.set_review("3965", "current", &input).unwrap();
+    let body: serde_json::Value = serde_json::from_slice(mock.requests()[0].body.as_ref().unwrap()).unwrap();
+    assert_eq!(body["tag"], "autogenerated:lint");
+  }
+
+  #[test]
+  fn set_review_with_overlapping_fix_is_not_sent() {
+    let mock = MockTransport::new();
+    let replacement = |start_line, end_line| FixReplacementInfo {
+      path: "lib.rs".into(),
+      range: CommentRange {
+        start_line,
+        start_character: 0,
+        end_line,
+        end_character: 0,
+      },
+      replacement: String::new(),
+    };
+    let mut input = review();
+    RobotCommentBuilder::new("lib.rs", "clippy", "run-1", "dead code")
+      .fix("Remove dead code", vec![replacement(1, 3)])
+      .attach_to(&mut input)
+      .unwrap();
+    input.robot_comments.as_mut().unwrap().get_mut("lib.rs").unwrap()[0]
+      .fix_suggestions
+      .as_mut()
+      .unwrap()
+      .push(FixSuggestionInfo {
+        fix_id: None,
+        description: "Remove dead code again".into(),
+        replacements: vec![replacement(1, 4), replacement(2, 5)],
+      });
+    let error = mock.gerrit().set_review("3965", "current", &input).unwrap_err();
+    assert!(matches!(error, crate::error::Error::WrongQuery(_)));
+    assert!(mock.requests().is_empty());
+  }
+
+  const DRAFT: &str = r#"{
+    "id": "TvcXrmjM",
+    "path": "gerrit-server/src/main/java/com/google/gerrit/server/project/RefControl.java",
+    "line": 23,
+    "message": "[nit] trailing whitespace",
+    "updated": "2013-02-26 15:40:43.986000000"
+  }"#;
+
+  fn draft_input(id: Option<&str>) -> CommentInput {
+    CommentInput {
+      id: id.map(String::from),
+      path: Some("gerrit-server/src/main/java/com/google/gerrit/server/project/RefControl.java".into()),
+      side: None,
+      line: Some(23),
+      range: None,
+      in_reply_to: None,
+      updated: None,
+      message: Some("[nit] trailing whitespace".into()),
+      tag: None,
+      unresolved: None,
+    }
+  }
+
+  #[test]
+  fn create_and_update_draft() {
+    let mock = MockTransport::new().respond_json(201, DRAFT).respond_json(200, DRAFT);
+    let mut gerrit = mock.gerrit();
+    let draft = gerrit.create_draft("3965", "current", &draft_input(None)).unwrap();
+    assert_eq!(draft.id, "TvcXrmjM");
+    gerrit
+      .update_draft("3965", "current", &draft_input(Some(&draft.id)))
+      .unwrap();
+    let requests = mock.requests();
+    assert_eq!(requests[0].method, "PUT");
+    assert_eq!(requests[0].path, "a/changes/3965/revisions/current/drafts");
+    assert_eq!(requests[1].method, "PUT");
+    assert_eq!(requests[1].path, "a/changes/3965/revisions/current/drafts/TvcXrmjM");
+  }
+
+  #[test]
+  fn update_draft_requires_id() {
+    let mock = MockTransport::new();
+    let error = mock
+      .gerrit()
+      .update_draft("3965", "current", &draft_input(None))
+      .unwrap_err();
+    assert!(matches!(error, Error::InvalidInput(_)));
+    assert!(mock.requests().is_empty());
+  }
+
+  #[test]
+  fn list_and_get_comments() {
+    let mock = MockTransport::new()
+      .respond_json(
+        200,
+        &format!(
+          r#"{{ "gerrit-server/src/main/java/com/google/gerrit/server/project/RefControl.java": [{}] }}"#,
+          DRAFT
+        ),
+      )
+      .respond_json(200, DRAFT);
+    let mut gerrit = mock.gerrit();
+    let comments = gerrit.list_comments("3965", "current").unwrap();
+    assert_eq!(comments.values().flatten().count(), 1);
+    let comment = gerrit.get_comment("3965", "current", "TvcXrmjM").unwrap();
+    assert_eq!(comment.line, Some(23));
+    let requests = mock.requests();
+    assert_eq!(requests[0].path, "a/changes/3965/revisions/current/comments/");
+    assert_eq!(requests[1].path, "a/changes/3965/revisions/current/comments/TvcXrmjM");
+  }
+
+  #[test]
+  fn delete_comment() {
+    let mock = MockTransport::new().respond_json(200, DRAFT);
+    mock.gerrit().delete_comment("3965", "current", "TvcXrmjM").unwrap();
+    let requests = mock.requests();
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(
+      requests[0].path,
+      "a/changes/3965/revisions/current/comments/TvcXrmjM/delete"
+    );
+    assert_eq!(requests[0].body.as_deref(), Some(&b"{}"[..]));
+  }
+
+  #[test]
+  fn submit_preview() {
+    let mock = MockTransport::new().respond(200, "PK");
+    let bundle = mock
+      .gerrit()
+      .submit_preview("3965", "current", CompressFormat::Tgz)
+      .unwrap();
+    assert_eq!(bundle, b"PK");
+    assert_eq!(
+      mock.requests()[0].path,
+      "a/changes/3965/revisions/current/preview_submit?format=tgz"
+    );
+  }
+
+  #[test]
+  fn get_files_content_encodes_paths() {
+    let mock = MockTransport::new()
+      .respond(200, "SGVsbG8=\n")
+      .respond(404, "Not found: nope.txt\n")
+      .respond(200, "Ynll\n");
+    let contents = mock
+      .gerrit()
+      .get_files_content("3965", "current", &["docs/Read Me.md", "nope.txt", "src/lib.rs"])
+      .unwrap();
+    assert_eq!(contents.len(), 2);
+    assert_eq!(contents["docs/Read Me.md"], b"Hello");
+    assert_eq!(contents["src/lib.rs"], b"bye");
+    assert!(!contents.contains_key("nope.txt"));
+    let paths: Vec<String> = mock.requests().into_iter().map(|r| r.path).collect();
+    assert_eq!(
+      paths,
+      vec![
+        "a/changes/3965/revisions/current/files/docs%2FRead%20Me.md/content",
+        "a/changes/3965/revisions/current/files/nope.txt/content",
+        "a/changes/3965/revisions/current/files/src%2Flib.rs/content",
+      ]
+    );
+  }
+
+  #[test]
+  fn get_files_content_fails_on_server_error() {
+    let mock = MockTransport::new()
+      .respond(200, "SGVsbG8=\n")
+      .respond(500, "Internal server error\n");
+    let error = mock
+      .gerrit()
+      .get_files_content("3965", "current", &["README.md", "src/lib.rs", "src/main.rs"])
+      .unwrap_err();
+    assert_eq!(
+      error.kind(),
+      crate::error::ErrorKind::UnexpectedHttpResponse(StatusCode::INTERNAL_SERVER_ERROR)
+    );
+    assert_eq!(mock.requests().len(), 2);
+  }
+
+  #[test]
+  fn edit_file_endpoints_encode_paths() {
+    let mock = MockTransport::new().respond(204, "").respond(204, "");
+    let mut gerrit = mock.gerrit();
+    gerrit.put_edit_file("3965", "docs/Read Me.md", b"Hello").unwrap();
+    gerrit.delete_edit_file("3965", "docs/Read Me.md").unwrap();
+    let requests = mock.requests();
+    assert_eq!(requests[0].method, "PUT");
+    assert_eq!(requests[0].path, "a/changes/3965/edit/docs%2FRead%20Me.md");
+    assert_eq!(requests[0].body.as_deref(), Some(&b"Hello"[..]));
+    assert_eq!(requests[1].method, "DELETE");
+    assert_eq!(requests[1].path, "a/changes/3965/edit/docs%2FRead%20Me.md");
+  }
+
+  // ChangeEndpoints must stay usable as a trait object.
+  fn _obj(_: &mut dyn ChangeEndpoints) {}
+
+  #[test]
+  fn votes_through_trait_object() {
+    let mock = MockTransport::new()
+      .respond_json(200, r#"{ "Code-Review": 2, "Verified": 1 }"#)
+      .respond(204, "");
+    let mut gerrit = mock.gerrit();
+    let endpoints: &mut dyn ChangeEndpoints = &mut gerrit;
+    let votes = endpoints.list_votes("3965", "john.doe@example.com").unwrap();
+    assert_eq!(votes["Code-Review"], 2);
+    endpoints
+      .delete_vote("3965", "john.doe@example.com", "Code-Review", None)
+      .unwrap();
+    _obj(endpoints);
+    let requests = mock.requests();
+    assert_eq!(requests[0].path, "a/changes/3965/reviewers/john.doe@example.com/votes/");
+    assert_eq!(requests[1].method, "DELETE");
+    assert_eq!(
+      requests[1].path,
+      "a/changes/3965/reviewers/john.doe@example.com/votes/Code-Review"
+    );
+  }
+
+  #[test]
+  fn set_hashtags_sends_normalized_input() {
+    let mock = MockTransport::new().respond_json(200, r#"["bugfix", "feature"]"#);
+    let input = HashtagsInput {
+      add: Some(vec![" feature".into(), "feature ".into(), "bugfix".into()]),
+      remove: None,
+    };
+    let hashtags = mock.gerrit().set_hashtags("3965", &input).unwrap();
+    assert_eq!(hashtags, vec!["bugfix", "feature"]);
+    let requests = mock.requests();
+    assert_eq!(requests[0].path, "a/changes/3965/hashtags");
+    assert_eq!(
+      requests[0].body.as_deref(),
+      Some(&br#"{"add":["feature","bugfix"]}"#[..])
+    );
+  }
+
+  #[test]
+  fn abandon_merged_change_is_a_conflict() {
+    let mock = MockTransport::new().respond(409, "change is merged\n");
+    let input = AbandonInput {
+      message: None,
+      notify: None,
+      notify_details: None,
+    };
+    let error = mock.gerrit().abandon_change("3965", &input).unwrap_err();
+    assert!(error.is_conflict());
+    assert_eq!(error.to_string(), "Conflict: change is merged");
+    assert_eq!(
+      error.verbose(1).to_string(),
+      "Conflict: change is merged\nResponse body:\nchange is merged"
+    );
+    assert_eq!(mock.requests()[0].path, "a/changes/3965/abandon");
+  }
+
+  #[test]
+  fn list_change_comments_groups_threads_per_file() {
+    let mock = MockTransport::new().respond_json(
+      200,
+      r#"{
+        "gerrit-server/src/main/java/com/google/gerrit/server/project/RefControl.java": [
+          {
+            "patch_set": 1,
+            "id": "TvcXrmjM",
+            "line": 23,
+            "message": "[nit] trailing whitespace",
+            "updated": "2013-02-26 15:40:43.986000000"
+          },
+          {
+            "patch_set": 1,
+            "id": "TveXwFiA",
+            "line": 23,
+            "in_reply_to": "TvcXrmjM",
+            "message": "Done",
+            "updated": "2013-02-26 15:49:54.000000000"
+          }
+        ]
+      }"#,
+    );
+    let comments = mock.gerrit().list_change_comments("3965").unwrap();
+    assert_eq!(comments.len(), 1);
+    let thread = &comments["gerrit-server/src/main/java/com/google/gerrit/server/project/RefControl.java"];
+    assert_eq!(thread.len(), 2);
+    assert_eq!(thread[0].id, "TvcXrmjM");
+    assert_eq!(thread[1].in_reply_to.as_deref(), Some("TvcXrmjM"));
+    assert_eq!(mock.requests()[0].path, "a/changes/3965/comments");
+  }
+
+  #[test]
+  fn list_change_robot_comments_per_file() {
+    let mock = MockTransport::new().respond_json(
+      200,
+      r#"{
+        "main.c": [
+          {
+            "id": "TvcXrmjM",
+            "line": 10,
+            "message": "unused variable",
+            "updated": "2013-02-26 15:40:43.986000000",
+            "robot_id": "lint",
+            "robot_run_id": "1",
+            "fix_suggestions": []
+          },
+          {
+            "id": "TvcXrmjN",
+            "line": 12,
+            "message": "missing semicolon",
+            "updated": "2013-02-26 15:40:43.986000000",
+            "robot_id": "lint",
+            "robot_run_id": "1",
+            "fix_suggestions": []
+          }
+        ]
+      }"#,
+    );
+    let comments = mock.gerrit().list_change_robot_comments("3965").unwrap();
+    let file = &comments["main.c"];
+    assert_eq!(file.len(), 2);
+    assert_eq!(file[1].comment.line, Some(12));
+    assert_eq!(file[1].robot_id, "lint");
+  }
+
+  #[test]
+  fn download_patch_writes_decoded_file() {
+    let patch = "From 1234 Mon Sep 17 00:00:00 2001\nSubject: [PATCH] Fix\n";
+    let mock = MockTransport::new().respond(200, &base64::encode(patch));
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("change.patch");
+    let written = mock.gerrit().download_patch("3965", "current", &None, &path).unwrap();
+    assert_eq!(written, patch.len());
+    assert_eq!(std::fs::read(&path).unwrap(), patch.as_bytes());
+    assert_eq!(mock.requests()[0].path, "a/changes/3965/revisions/current/patch");
+  }
+
+  #[test]
+  fn edit_message_round_trip() {
+    let input = ChangeEditMessageInput {
+      message: "Fix the build\n".to_string(),
+    };
+    let encoded = serde_json::to_string(&base64::encode(&input.message)).unwrap();
+    let mock = MockTransport::new().respond(204, "").respond_json(200, &encoded);
+    let mut gerrit = mock.gerrit();
+    gerrit.set_edit_message("3965", &input).unwrap();
+    assert_eq!(gerrit.get_edit_message("3965").unwrap(), input.message);
+    let requests = mock.requests();
+    assert_eq!(requests[0].method, "PUT");
+    assert_eq!(requests[0].path, "a/changes/3965/edit:message");
+    let sent: ChangeEditMessageInput = serde_json::from_slice(requests[0].body.as_ref().unwrap()).unwrap();
+    assert_eq!(sent.message, input.message);
+    assert_eq!(requests[1].method, "GET");
+    assert_eq!(requests[1].path, "a/changes/3965/edit:message");
+  }
+
+  #[test]
+  fn edit_message_rejects_invalid_utf8() {
+    let encoded = serde_json::to_string(&base64::encode(&[0x46, 0x69, 0x78, 0xff])).unwrap();
+    let mock = MockTransport::new().respond_json(200, &encoded);
+    let error = mock.gerrit().get_edit_message("3965").unwrap_err();
+    assert!(matches!(error, Error::InvalidUtf8Response(_)));
+  }
+
+  #[test]
+  fn delete_all_drafts_across_files() {
+    let drafts = r#"{
+      "gerrit-server/src/main/java/com/google/gerrit/server/project/RefControl.java": [
+        { "id": "TvcXrmjM", "patch_set": 1, "line": 23, "message": "[nit] trailing whitespace", "updated": "2013-02-26 15:40:43.986000000" }
+      ],
+      "README.md": [
+        { "id": "TveXwFiA", "patch_set": 2, "line": 5, "message": "typo", "updated": "2013-02-26 15:40:45.328000000" }
+      ]
+    }"#;
+    let mock = MockTransport::new()
+      .respond_json(200, drafts)
+      .respond(204, "")
+      .respond(204, "");
+    assert_eq!(mock.gerrit().delete_all_drafts("3965").unwrap(), 2);
+    let requests: Vec<(&str, String)> = mock.requests().into_iter().map(|r| (r.method, r.path)).collect();
+    assert_eq!(
+      requests,
+      vec![
+        ("GET", "a/changes/3965/drafts".to_string()),
+        ("DELETE", "a/changes/3965/revisions/2/drafts/TveXwFiA".to_string()),
+        ("DELETE", "a/changes/3965/revisions/1/drafts/TvcXrmjM".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn abandon_matching_all_pages() {
+    let change = |number: u32, more_changes: bool| {
+      let mut change: serde_json::Value = serde_json::from_str(CHANGE).unwrap();
+      change["id"] = format!("myProject~master~I{}", number).into();
+      change["_number"] = number.into();
+      if more_changes {
+        change["_more_changes"] = true.into();
+      }
+      change
+    };
+    let page1 = serde_json::json!([change(1, false), change(2, true)]);
+    let page2 = serde_json::json!([change(3, false)]);
+    let mock = MockTransport::new()
+      .respond_json(200, &page1.to_string())
+      .respond_json(200, &page2.to_string())
+      .respond_json(200, &change(1, false).to_string())
+      .respond(409, "change is merged\n")
+      .respond_json(200, &change(3, false).to_string());
+    let input = AbandonInput {
+      message: None,
+      notify: None,
+      notify_details: None,
+    };
+    let results = mock
+      .gerrit()
+      .abandon_matching(&QueryStr::Raw("topic:cleanup".into()), &input)
+      .unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].0, 1);
+    assert_eq!(results[0].1.as_ref().unwrap().number, 1);
+    assert_eq!(results[1].0, 2);
+    assert!(results[1].1.as_ref().unwrap_err().is_conflict());
+    assert_eq!(results[2].0, 3);
+    assert!(results[2].1.is_ok());
+    let requests: Vec<(&str, String)> = mock.requests().into_iter().map(|r| (r.method, r.path)).collect();
+    assert_eq!(
+      requests,
+      vec![
+        ("GET", "a/changes/?q=topic%3Acleanup&S=0".to_string()),
+        ("GET", "a/changes/?q=topic%3Acleanup&S=2".to_string()),
+        ("POST", "a/changes/myProject~master~I1/abandon".to_string()),
+        ("POST", "a/changes/myProject~master~I2/abandon".to_string()),
+        ("POST", "a/changes/myProject~master~I3/abandon".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn get_mergeable_with_conflicts() {
+    let mock = MockTransport::new().respond_json(
+      200,
+      r#"{
+        "submit_type": "MERGE_IF_NECESSARY",
+        "strategy": "recursive",
+        "mergeable": false,
+        "conflicts": ["src/lib.rs", "README.md"],
+        "mergeable_into": ["stable-2.20"]
+      }"#,
+    );
+    let mergeable = mock.gerrit().get_mergeable("3965", "current", true).unwrap();
+    assert!(!mergeable.mergeable);
+    assert_eq!(mergeable.conflicts.unwrap(), vec!["src/lib.rs", "README.md"]);
+    assert_eq!(mergeable.mergeable_into.unwrap(), vec!["stable-2.20"]);
+    assert_eq!(
+      mock.requests()[0].path,
+      "a/changes/3965/revisions/current/mergeable?other-branches"
+    );
+  }
+
+  #[test]
+  fn get_or_compute_mergeable() {
+    let mut known: serde_json::Value = serde_json::from_str(CHANGE).unwrap();
+    known["mergeable"] = true.into();
+    let mock = MockTransport::new().respond_json(200, &known.to_string());
+    assert!(mock.gerrit().get_or_compute_mergeable("3965").unwrap());
+    assert_eq!(mock.requests().len(), 1);
+    assert_eq!(mock.requests()[0].path, "a/changes/3965/");
+
+    let mock = MockTransport::new()
+      .respond_json(200, CHANGE)
+      .respond_json(200, r#"{ "submit_type": "MERGE_IF_NECESSARY", "mergeable": false }"#);
+    assert!(!mock.gerrit().get_or_compute_mergeable("3965").unwrap());
+    let paths: Vec<String> = mock.requests().into_iter().map(|r| r.path).collect();
+    assert_eq!(
+      paths,
+      vec!["a/changes/3965/", "a/changes/3965/revisions/current/mergeable"]
+    );
+  }
+
+  fn cherry_pick_input(allow_conflicts: Option<bool>) -> CherryPickInput {
+    CherryPickInput {
+      message: None,
+      destination: "stable-2.20".into(),
+      base: None,
+      parent: None,
+      notify: None,
+      notify_details: None,
+      keep_reviewers: None,
+      allow_conflicts,
+    }
+  }
+
+  #[test]
+  fn cherry_pick_clean() {
+    let mut picked: serde_json::Value = serde_json::from_str(CHANGE).unwrap();
+    picked["branch"] = "stable-2.20".into();
+    picked["_number"] = 3966.into();
+    let mock = MockTransport::new().respond_json(200, &picked.to_string());
+    let change = mock
+      .gerrit()
+      .cherry_pick("3965", "current", &cherry_pick_input(None))
+      .unwrap();
+    assert_eq!(change.number, 3966);
+    assert_eq!(change.branch, "stable-2.20");
+    assert!(!change.contains_git_conflicts);
+    let request = &mock.requests()[0];
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "a/changes/3965/revisions/current/cherrypick");
+    assert_eq!(request.body.as_deref(), Some(&br#"{"destination":"stable-2.20"}"#[..]));
+  }
+
+  #[test]
+  fn cherry_pick_conflict() {
+    let mock = MockTransport::new().respond(409, "Cherry pick failed: merge conflict\n");
+    let error = mock
+      .gerrit()
+      .cherry_pick("3965", "current", &cherry_pick_input(Some(false)))
+      .unwrap_err();
+    assert!(error.is_conflict());
+    assert_eq!(error.to_string(), "Conflict: Cherry pick failed: merge conflict");
+    assert_eq!(
+      mock.requests()[0].body.as_deref(),
+      Some(&br#"{"destination":"stable-2.20","allow_conflicts":false}"#[..])
+    );
+  }
+
+  #[test]
+  fn edit_message_multi_line_with_change_id() {
+    let message = "Fix the build on Windows\n\
+                   \n\
+                   Paths were joined with a hard-coded slash.\n\
+                   Use Path::join instead.\n\
+                   \n\
+                   Bug: Issue 42\n\
+                   Change-Id: I8473b95934b5732ac55d26311a706c9c2bde9940\n";
+    let encoded = serde_json::to_string(&base64::encode(message)).unwrap();
+    let mock = MockTransport::new().respond(204, "").respond_json(200, &encoded);
+    let mut gerrit = mock.gerrit();
+    let input = ChangeEditMessageInput {
+      message: message.to_string(),
+    };
+    gerrit.set_edit_message("3965", &input).unwrap();
+    let sent: ChangeEditMessageInput = serde_json::from_slice(mock.requests()[0].body.as_ref().unwrap()).unwrap();
+    assert_eq!(sent.message, message);
+    let received = gerrit.get_edit_message("3965").unwrap();
+    assert_eq!(received, message);
+    assert_eq!(
+      received.lines().last(),
+      Some("Change-Id: I8473b95934b5732ac55d26311a706c9c2bde9940")
+    );
+  }
+
+  #[test]
+  fn revision_file_queries() {
+    let mock = MockTransport::new()
+      .respond_json(200, "{}")
+      .respond_json(200, "{}")
+      .respond_json(200, r#"["src/lib.rs"]"#)
+      .respond_json(
+        200,
+        r#"{ "change_type": "MODIFIED", "diff_header": [], "content": [] }"#,
+      );
+    let mut gerrit = mock.gerrit();
+    gerrit.list_files("3965", "current", &None).unwrap();
+    let base = ListFilesParams {
+      base: Some(1),
+      ..Default::default()
+    };
+    gerrit.list_files("3965", "current", &Some(base)).unwrap();
+    let reviewed = ListFilesParams {
+      reviewed: Some(()),
+      ..Default::default()
+    };
+    assert_eq!(
+      gerrit.list_file_paths("3965", "current", &reviewed).unwrap(),
+      vec!["src/lib.rs"]
+    );
+    let diff = DiffParams {
+      intraline: Some(()),
+      whitespace: Some(DiffWhitespace::IgnoreAll),
+      ..Default::default()
+    };
+    gerrit.get_diff("3965", "current", "src%2Flib.rs", &Some(diff)).unwrap();
+    let paths: Vec<String> = mock.requests().into_iter().map(|r| r.path).collect();
+    assert_eq!(
+      paths,
+      vec![
+        "a/changes/3965/revisions/current/files/",
+        "a/changes/3965/revisions/current/files/?base=1",
+        "a/changes/3965/revisions/current/files/?reviewed=",
+        "a/changes/3965/revisions/current/files/src%2Flib.rs/diff?intraline=&whitespace=IGNORE_ALL",
+      ]
+    );
   }
 }
@@ -0,0 +1,96 @@
+//! Config Endpoint implementation.
+
+use crate::config::*;
+use crate::http::Transport;
+use crate::{GerritRestApi, Result};
+use ::http::StatusCode;
+
+/// Implement trait [ConfigEndpoints](trait.ConfigEndpoints.html) for Gerrit REST API.
+impl<T: Transport> ConfigEndpoints for GerritRestApi<T> {
+  fn get_version(&mut self) -> Result<String> {
+    let json = self
+      .rest
+      .get("a/config/server/version")?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let version = serde_json::from_str(&json)?;
+    Ok(version)
+  }
+
+  fn get_server_info(&mut self) -> Result<ServerInfo> {
+    let json = self.rest.get("a/config/server/info")?.expect(StatusCode::OK)?.json()?;
+    let info = serde_json::from_str(&json)?;
+    Ok(info)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::http::mock::MockTransport;
+
+  const SERVER_INFO: &str = r#"{
+    "accounts": { "visibility": "ALL", "default_display_name": "FULL_NAME" },
+    "auth": {
+      "auth_type": "LDAP",
+      "use_contributor_agreements": true,
+      "editable_account_fields": ["FULL_NAME", "REGISTER_NEW_EMAIL"],
+      "register_url": "https://accounts.example.com/register",
+      "git_basic_auth_policy": "LDAP"
+    },
+    "change": {
+      "allow_blame": true,
+      "large_change": 500,
+      "reply_label": "Reply",
+      "reply_tooltip": "Reply and score",
+      "update_delay": 300,
+      "submit_whole_topic": true
+    },
+    "download": {
+      "schemes": {
+        "ssh": {
+          "url": "ssh://jdoe@gerrit.example.com:29418/${project}",
+          "is_auth_required": true,
+          "is_auth_supported": true,
+          "commands": {
+            "Checkout": "git fetch ssh://jdoe@gerrit.example.com:29418/${project} ${ref} && git checkout FETCH_HEAD"
+          },
+          "clone_commands": { "Clone": "git clone ssh://jdoe@gerrit.example.com:29418/${project}" }
+        }
+      },
+      "archives": ["tgz", "tar"]
+    },
+    "gerrit": {
+      "all_projects": "All-Projects",
+      "all_users": "All-Users",
+      "doc_search": true,
+      "doc_url": "https://gerrit-review.googlesource.com/Documentation/"
+    },
+    "plugin": { "has_avatars": false, "js_resource_paths": [] },
+    "suggest": { "from": 0 },
+    "user": { "anonymous_coward_name": "Name of user not set" }
+  }"#;
+
+  #[test]
+  fn get_server_info() {
+    let mock = MockTransport::new().respond_json(200, SERVER_INFO);
+    let info = mock.gerrit().get_server_info().unwrap();
+    assert_eq!(mock.requests()[0].path, "a/config/server/info");
+    assert_eq!(
+      info.gerrit.doc_url.as_deref(),
+      Some("https://gerrit-review.googlesource.com/Documentation/")
+    );
+    assert_eq!(info.gerrit.all_projects, "All-Projects");
+    assert!(info.gerrit.doc_search);
+    assert!(!info.gerrit.edit_gpg_keys);
+    assert_eq!(info.auth.auth_type, "LDAP");
+    assert!(info.auth.use_contributor_agreements);
+    assert!(info.auth.login_url.is_none());
+    assert_eq!(info.accounts.default_display_name.as_deref(), Some("FULL_NAME"));
+    assert_eq!(info.change.large_change, 500);
+    assert!(!info.change.disable_private_changes);
+    assert_eq!(info.download.archives, vec!["tgz", "tar"]);
+    assert!(info.download.schemes["ssh"].is_auth_required);
+    assert_eq!(info.user.anonymous_coward_name, "Name of user not set");
+  }
+}
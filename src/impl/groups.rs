@@ -0,0 +1,248 @@
+//! Group Endpoint implementation.
+
+use crate::accounts::AccountInfo;
+use crate::groups::*;
+use crate::handler::encode_path_segment;
+use crate::http::Transport;
+use crate::{GerritRestApi, Result};
+use ::http::StatusCode;
+use std::collections::BTreeMap;
+
+/// Implement trait [GroupEndpoints](trait.GroupEndpoints.html) for Gerrit REST API.
+impl<T: Transport> GroupEndpoints for GerritRestApi<T> {
+  fn list_groups(&mut self, params: &GroupQueryParams) -> Result<BTreeMap<String, GroupInfo>> {
+    let json = self
+      .rest
+      .get_with_query("a/groups/", params)?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let groups = serde_json::from_str(&json)?;
+    Ok(groups)
+  }
+
+  fn get_group(&mut self, group_id: &str) -> Result<GroupInfo> {
+    let json = self
+      .rest
+      .get(format!("a/groups/{}", encode_path_segment(group_id)).as_str())?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let group = serde_json::from_str(&json)?;
+    Ok(group)
+  }
+
+  fn create_group(&mut self, name: &str, input: &GroupInput) -> Result<GroupInfo> {
+    let json = self
+      .rest
+      .put_json(format!("a/groups/{}", encode_path_segment(name)).as_str(), input)?
+      .expect(StatusCode::CREATED)?
+      .json()?;
+    let group = serde_json::from_str(&json)?;
+    Ok(group)
+  }
+
+  fn get_group_detail(&mut self, group_id: &str) -> Result<GroupInfo> {
+    let json = self
+      .rest
+      .get(format!("a/groups/{}/detail", encode_path_segment(group_id)).as_str())?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let group = serde_json::from_str(&json)?;
+    Ok(group)
+  }
+
+  fn rename_group(&mut self, group_id: &str, name: &str) -> Result<String> {
+    let input = GroupNameInput { name: name.into() };
+    let json = self
+      .rest
+      .put_json(
+        format!("a/groups/{}/name", encode_path_segment(group_id)).as_str(),
+        &input,
+      )?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let name = serde_json::from_str(&json)?;
+    Ok(name)
+  }
+
+  fn set_group_description(&mut self, group_id: &str, description: Option<&str>) -> Result<Option<String>> {
+    let input = GroupDescriptionInput {
+      description: description.map(Into::into),
+    };
+    let response = self.rest.put_json(
+      format!("a/groups/{}/description", encode_path_segment(group_id)).as_str(),
+      &input,
+    )?;
+    match response.code {
+      StatusCode::OK => Ok(Some(serde_json::from_str(&response.message.json()?)?)),
+      StatusCode::NO_CONTENT => Ok(None),
+      _ => Err(response.into_error()),
+    }
+  }
+
+  fn list_members(&mut self, group_id: &str, recursive: bool) -> Result<Vec<AccountInfo>> {
+    let url = format!(
+      "a/groups/{}/members/{}",
+      encode_path_segment(group_id),
+      if recursive { "?recursive" } else { "" }
+    );
+    let json = self.rest.get(&url)?.expect(StatusCode::OK)?.json()?;
+    let members = serde_json::from_str(&json)?;
+    Ok(members)
+  }
+
+  fn add_member(&mut self, group_id: &str, account_id: &str) -> Result<AccountInfo> {
+    let response = self.rest.put(
+      format!(
+        "a/groups/{}/members/{}",
+        encode_path_segment(group_id),
+        encode_path_segment(account_id)
+      )
+      .as_str(),
+    )?;
+    match response.code {
+      StatusCode::OK | StatusCode::CREATED => Ok(serde_json::from_str(&response.message.json()?)?),
+      _ => Err(response.into_error()),
+    }
+  }
+
+  fn add_members(&mut self, group_id: &str, input: &MembersInput) -> Result<Vec<AccountInfo>> {
+    let json = self
+      .rest
+      .post_json(
+        format!("a/groups/{}/members.add", encode_path_segment(group_id)).as_str(),
+        input,
+      )?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let members = serde_json::from_str(&json)?;
+    Ok(members)
+  }
+
+  fn remove_member(&mut self, group_id: &str, account_id: &str) -> Result<()> {
+    self
+      .rest
+      .delete(
+        format!(
+          "a/groups/{}/members/{}",
+          encode_path_segment(group_id),
+          encode_path_segment(account_id)
+        )
+        .as_str(),
+      )?
+      .expect(StatusCode::NO_CONTENT)?;
+    Ok(())
+  }
+
+  fn remove_members(&mut self, group_id: &str, input: &MembersInput) -> Result<()> {
+    self
+      .rest
+      .post_json(
+        format!("a/groups/{}/members.delete", encode_path_segment(group_id)).as_str(),
+        input,
+      )?
+      .expect(StatusCode::NO_CONTENT)?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::http::mock::MockTransport;
+
+  const GROUP: &str = r#"{
+    "id": "6a1e70e1a88782771a91808c8af9bbb7a9871389",
+    "name": "Project Owners",
+    "options": { "visible_to_all": true },
+    "description": "Owners of the project",
+    "group_id": 7,
+    "owner": "Project Owners",
+    "owner_id": "6a1e70e1a88782771a91808c8af9bbb7a9871389",
+    "created_on": "2013-02-01 09:59:32.126000000"
+  }"#;
+
+  #[test]
+  fn create_group_encodes_name() {
+    let mock = MockTransport::new().respond_json(201, GROUP);
+    let input = GroupInput {
+      description: Some("Owners of the project".into()),
+      visible_to_all: Some(true),
+      ..Default::default()
+    };
+    let group = mock.gerrit().create_group("Project Owners", &input).unwrap();
+    assert_eq!(group.name.as_deref(), Some("Project Owners"));
+    assert_eq!(group.group_id, Some(7));
+    assert!(group.options.visible_to_all);
+    let requests = mock.requests();
+    assert_eq!(requests[0].method, "PUT");
+    assert_eq!(requests[0].path, "a/groups/Project%20Owners");
+  }
+
+  #[test]
+  fn list_members_encodes_group_id() {
+    let members = r#"[
+      { "_account_id": 1000096, "name": "John Doe", "email": "john.doe@example.com", "username": "john" },
+      { "_account_id": 1000097, "name": "Jane Roe", "email": "jane.roe@example.com", "username": "jane" }
+    ]"#;
+    let mock = MockTransport::new()
+      .respond_json(200, members)
+      .respond_json(200, members);
+    let mut gerrit = mock.gerrit();
+    let members = gerrit.list_members("Project Owners", false).unwrap();
+    assert_eq!(members.len(), 2);
+    assert_eq!(members[1].username.as_deref(), Some("jane"));
+    gerrit.list_members("Project Owners", true).unwrap();
+    let requests = mock.requests();
+    assert_eq!(requests[0].method, "GET");
+    assert_eq!(requests[0].path, "a/groups/Project%20Owners/members/");
+    assert_eq!(requests[1].path, "a/groups/Project%20Owners/members/?recursive");
+  }
+
+  #[test]
+  fn member_endpoints_encode_account_id() {
+    let jane = r#"{ "_account_id": 1000097, "email": "jane.roe+ci@example.com" }"#;
+    let mock = MockTransport::new()
+      .respond_json(201, jane)
+      .respond_json(200, &format!("[{}]", jane))
+      .respond(204, "")
+      .respond(204, "");
+    let mut gerrit = mock.gerrit();
+    let input = MembersInput {
+      members: vec!["jane.roe+ci@example.com".into()],
+    };
+    let member = gerrit.add_member("Project Owners", "jane.roe+ci@example.com").unwrap();
+    assert_eq!(member.account_id, 1000097);
+    assert_eq!(gerrit.add_members("Project Owners", &input).unwrap().len(), 1);
+    gerrit
+      .remove_member("Project Owners", "jane.roe+ci@example.com")
+      .unwrap();
+    gerrit.remove_members("Project Owners", &input).unwrap();
+    let requests: Vec<(&str, String)> = mock.requests().into_iter().map(|r| (r.method, r.path)).collect();
+    assert_eq!(
+      requests,
+      vec![
+        (
+          "PUT",
+          "a/groups/Project%20Owners/members/jane.roe%2Bci%40example.com".to_string()
+        ),
+        ("POST", "a/groups/Project%20Owners/members.add".to_string()),
+        (
+          "DELETE",
+          "a/groups/Project%20Owners/members/jane.roe%2Bci%40example.com".to_string()
+        ),
+        ("POST", "a/groups/Project%20Owners/members.delete".to_string()),
+      ]
+    );
+    let body = br#"{"members":["jane.roe+ci@example.com"]}"#;
+    assert_eq!(mock.requests()[1].body.as_deref(), Some(&body[..]));
+    assert_eq!(mock.requests()[3].body.as_deref(), Some(&body[..]));
+  }
+
+  #[test]
+  fn list_groups_without_params() {
+    let mock = MockTransport::new().respond_json(200, &format!(r#"{{ "Project Owners": {} }}"#, GROUP));
+    let groups = mock.gerrit().list_groups(&GroupQueryParams::default()).unwrap();
+    assert_eq!(groups["Project Owners"].group_id, Some(7));
+    assert_eq!(mock.requests()[0].path, "a/groups/");
+  }
+}
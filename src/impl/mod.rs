@@ -1,3 +1,7 @@
 //! Gerrit REST API endpoint implementation.
 
+mod accounts;
 mod changes;
+mod config;
+mod groups;
+mod projects;
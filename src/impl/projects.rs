@@ -0,0 +1,367 @@
+//! Project Endpoint implementation.
+
+use crate::handler::encode_path_segment;
+use crate::http::Transport;
+use crate::projects::*;
+use crate::{GerritRestApi, Result};
+use ::http::StatusCode;
+use std::collections::BTreeMap;
+
+/// Implement trait [ProjectEndpoints](trait.ProjectEndpoints.html) for Gerrit REST API.
+impl<T: Transport> ProjectEndpoints for GerritRestApi<T> {
+  fn list_projects(&mut self, params: &ProjectQueryParams) -> Result<BTreeMap<String, ProjectInfo>> {
+    let json = self
+      .rest
+      .get_with_query("a/projects/", params)?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let mut projects = serde_json::from_str(&json)?;
+    fill_project_names(&mut projects);
+    Ok(projects)
+  }
+
+  fn get_project(&mut self, name: &str) -> Result<ProjectInfo> {
+    let json = self
+      .rest
+      .get(format!("a/projects/{}", encode_path_segment(name)).as_str())?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let project = serde_json::from_str(&json)?;
+    Ok(project)
+  }
+
+  fn create_project(&mut self, name: &str, input: &ProjectInput) -> Result<ProjectInfo> {
+    let json = self
+      .rest
+      .put_json(format!("a/projects/{}", encode_path_segment(name)).as_str(), input)?
+      .expect(StatusCode::CREATED)?
+      .json()?;
+    let project = serde_json::from_str(&json)?;
+    Ok(project)
+  }
+
+  fn get_head(&mut self, project: &str) -> Result<String> {
+    let json = self
+      .rest
+      .get(format!("a/projects/{}/HEAD", encode_path_segment(project)).as_str())?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let head = serde_json::from_str(&json)?;
+    Ok(head)
+  }
+
+  fn set_head(&mut self, project: &str, input: &HeadInput) -> Result<String> {
+    input.validate()?;
+    let json = self
+      .rest
+      .put_json(
+        format!("a/projects/{}/HEAD", encode_path_segment(project)).as_str(),
+        input,
+      )?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let head = serde_json::from_str(&json)?;
+    Ok(head)
+  }
+
+  fn get_project_config(&mut self, project: &str) -> Result<ConfigInfo> {
+    let json = self
+      .rest
+      .get(format!("a/projects/{}/config", encode_path_segment(project)).as_str())?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let config = serde_json::from_str(&json)?;
+    Ok(config)
+  }
+
+  fn set_project_config(&mut self, project: &str, input: &ConfigInput) -> Result<ConfigInfo> {
+    let json = self
+      .rest
+      .put_json(
+        format!("a/projects/{}/config", encode_path_segment(project)).as_str(),
+        input,
+      )?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let config = serde_json::from_str(&json)?;
+    Ok(config)
+  }
+
+  fn create_tag(&mut self, project: &str, tag: &str, input: &TagInput) -> Result<TagInfo> {
+    input.validate(tag)?;
+    let json = self
+      .rest
+      .put_json(
+        format!(
+          "a/projects/{}/tags/{}",
+          encode_path_segment(project),
+          encode_path_segment(tag)
+        )
+        .as_str(),
+        input,
+      )?
+      .expect(StatusCode::CREATED)?
+      .json()?;
+    let tag = serde_json::from_str(&json)?;
+    Ok(tag)
+  }
+
+  fn get_access(&mut self, project: &str) -> Result<ProjectAccessInfo> {
+    let json = self
+      .rest
+      .get(format!("a/projects/{}/access", encode_path_segment(project)).as_str())?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let access = serde_json::from_str(&json)?;
+    Ok(access)
+  }
+
+  fn set_access(&mut self, project: &str, input: &ProjectAccessInput) -> Result<ProjectAccessInfo> {
+    let json = self
+      .rest
+      .post_json(
+        format!("a/projects/{}/access", encode_path_segment(project)).as_str(),
+        input,
+      )?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let access = serde_json::from_str(&json)?;
+    Ok(access)
+  }
+
+  fn check_access(&mut self, project: &str, input: &CheckAccessInput) -> Result<AccessCheckInfo> {
+    let json = self
+      .rest
+      .get_with_query(
+        &format!("a/projects/{}/check.access", encode_path_segment(project)),
+        input,
+      )?
+      .expect(StatusCode::OK)?
+      .json()?;
+    let check = serde_json::from_str(&json)?;
+    Ok(check)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::error::Error;
+  use crate::http::mock::MockTransport;
+
+  #[test]
+  fn list_projects() {
+    let mock = MockTransport::new().respond_json(
+      200,
+      r#"{
+        "platform/build": { "id": "platform%2Fbuild", "state": "ACTIVE" },
+        "platform/manifest": { "id": "platform%2Fmanifest", "state": "READ_ONLY" }
+      }"#,
+    );
+    let params = ProjectQueryParams {
+      prefix: Some("platform/".into()),
+      limit: Some(2),
+      ..Default::default()
+    };
+    let projects = mock.gerrit().list_projects(&params).unwrap();
+    assert_eq!(projects.len(), 2);
+    assert_eq!(projects["platform/build"].id, "platform%2Fbuild");
+    assert_eq!(projects["platform/manifest"].state, Some(ProjectStatus::ReadOnly));
+    assert_eq!(mock.requests()[0].path, "a/projects/?p=platform%2F&n=2");
+  }
+
+  #[test]
+  fn list_projects_fills_names() {
+    let mock = MockTransport::new().respond_json(
+      200,
+      r#"{
+        "All-Projects": { "id": "All-Projects" },
+        "platform/build": { "id": "platform%2Fbuild", "name": "platform/build" }
+      }"#,
+    );
+    let projects = mock.gerrit().list_projects(&Default::default()).unwrap();
+    assert_eq!(projects["All-Projects"].name.as_deref(), Some("All-Projects"));
+    assert_eq!(projects["platform/build"].name.as_deref(), Some("platform/build"));
+  }
+
+  #[test]
+  fn check_access() {
+    let mock = MockTransport::new()
+      .respond_json(200, r#"{ "status": 200 }"#)
+      .respond_json(
+        200,
+        r#"{
+          "status": 403,
+          "message": "user jdoe (1000096) cannot see ref refs/heads/secret in project platform/build",
+          "debug_logs": ["'jdoe' cannot perform 'read' on refs/heads/secret"]
+        }"#,
+      );
+    let mut gerrit = mock.gerrit();
+    let input = CheckAccessInput {
+      account: "jdoe".into(),
+      refspec: Some("refs/heads/master".into()),
+      permission: None,
+    };
+    let allowed = gerrit.check_access("platform/build", &input).unwrap();
+    assert!(allowed.is_allowed());
+    assert_eq!(allowed.message, None);
+    let input = CheckAccessInput {
+      refspec: Some("refs/heads/secret".into()),
+      ..input
+    };
+    let denied = gerrit.check_access("platform/build", &input).unwrap();
+    assert!(!denied.is_allowed());
+    assert_eq!(denied.status, 403);
+    assert_eq!(
+      denied.message.as_deref(),
+      Some("user jdoe (1000096) cannot see ref refs/heads/secret in project platform/build")
+    );
+    assert_eq!(denied.debug_logs.unwrap().len(), 1);
+    assert_eq!(
+      mock.requests()[1].path,
+      "a/projects/platform%2Fbuild/check.access?account=jdoe&ref=refs%2Fheads%2Fsecret"
+    );
+  }
+
+  #[test]
+  fn get_nested_project() {
+    let mock = MockTransport::new().respond_json(
+      200,
+      r#"{ "id": "platform%2Fbuild", "name": "platform/build", "parent": "All-Projects" }"#,
+    );
+    let project = mock.gerrit().get_project("platform/build").unwrap();
+    assert_eq!(project.name.as_deref(), Some("platform/build"));
+    assert_eq!(project.parent.as_deref(), Some("All-Projects"));
+    let requests = mock.requests();
+    assert_eq!(requests[0].method, "GET");
+    assert_eq!(requests[0].path, "a/projects/platform%2Fbuild");
+  }
+
+  #[test]
+  fn create_nested_project() {
+    let mock = MockTransport::new().respond_json(
+      201,
+      r#"{ "id": "platform%2Fbuild", "name": "platform/build", "description": "Build tools" }"#,
+    );
+    let input = ProjectInput {
+      description: Some("Build tools".into()),
+      create_empty_commit: Some(true),
+      ..Default::default()
+    };
+    let project = mock.gerrit().create_project("platform/build", &input).unwrap();
+    assert_eq!(project.description.as_deref(), Some("Build tools"));
+    let requests = mock.requests();
+    assert_eq!(requests[0].method, "PUT");
+    assert_eq!(requests[0].path, "a/projects/platform%2Fbuild");
+    assert_eq!(
+      requests[0].body.as_deref(),
+      Some(&br#"{"description":"Build tools","create_empty_commit":true}"#[..])
+    );
+  }
+
+  #[test]
+  fn create_existing_project_is_a_conflict() {
+    let mock = MockTransport::new().respond(409, "Project already exists\n");
+    let error = mock
+      .gerrit()
+      .create_project("platform/build", &ProjectInput::default())
+      .unwrap_err();
+    assert!(error.is_conflict());
+  }
+
+  #[test]
+  fn set_head() {
+    let mock = MockTransport::new().respond_json(200, r#""refs/heads/stable""#);
+    let input = HeadInput::new("refs/heads/stable").unwrap();
+    let head = mock.gerrit().set_head("gerlib", &input).unwrap();
+    assert_eq!(head, "refs/heads/stable");
+    let requests = mock.requests();
+    assert_eq!(requests[0].method, "PUT");
+    assert_eq!(requests[0].path, "a/projects/gerlib/HEAD");
+  }
+
+  #[test]
+  fn set_head_rejects_short_ref_before_sending() {
+    let mock = MockTransport::new();
+    let input = HeadInput {
+      refspec: "stable".into(),
+    };
+    let error = mock.gerrit().set_head("gerlib", &input).unwrap_err();
+    assert!(matches!(error, Error::InvalidInput(_)));
+    assert!(mock.requests().is_empty());
+  }
+
+  #[test]
+  fn create_annotated_tag() {
+    let mock = MockTransport::new().respond_json(
+      201,
+      r#"{
+        "ref": "refs/tags/v1.0",
+        "revision": "c628685b3c5a3614571ecb5c8fceb85db9112313",
+        "object": "1624f5af8ae89148d1a3730df8c290413e3dcf30",
+        "message": "Release 1.0",
+        "tagger": {
+          "name": "David Pursehouse",
+          "email": "dpursehouse@collab.net",
+          "date": "2016-06-14 01:52:10.000000000",
+          "tz": 540
+        }
+      }"#,
+    );
+    let tag = mock
+      .gerrit()
+      .create_tag("gerlib", "v1.0", &TagInput::annotated(None, "Release 1.0"))
+      .unwrap();
+    assert_eq!(tag.message.as_deref(), Some("Release 1.0"));
+    assert!(tag.tagger.is_some());
+    let requests = mock.requests();
+    assert_eq!(requests[0].method, "PUT");
+    assert_eq!(requests[0].path, "a/projects/gerlib/tags/v1.0");
+  }
+
+  #[test]
+  fn create_lightweight_tag() {
+    let mock = MockTransport::new().respond_json(
+      201,
+      r#"{ "ref": "refs/tags/v1.0", "revision": "1624f5af8ae89148d1a3730df8c290413e3dcf30" }"#,
+    );
+    let input = TagInput::lightweight(Some("1624f5af8ae89148d1a3730df8c290413e3dcf30".into()));
+    let tag = mock.gerrit().create_tag("gerlib", "v1.0", &input).unwrap();
+    assert_eq!(tag.object, None);
+    assert_eq!(tag.message, None);
+  }
+
+  #[test]
+  fn create_tag_with_blank_message_is_not_sent() {
+    let mock = MockTransport::new();
+    let input = TagInput::annotated(None, "");
+    assert!(mock.gerrit().create_tag("gerlib", "v1.0", &input).is_err());
+    assert!(mock.requests().is_empty());
+  }
+
+  #[test]
+  fn project_endpoints_encode_the_name() {
+    let mock = MockTransport::new()
+      .respond_json(200, r#""refs/heads/main""#)
+      .respond_json(200, "{}")
+      .respond_json(200, r#"{ "status": 200 }"#);
+    let mut gerrit = mock.gerrit();
+    gerrit.get_head("platform/build").unwrap();
+    gerrit.get_project_config("platform/build").unwrap();
+    let input = CheckAccessInput {
+      account: "1000096".into(),
+      refspec: None,
+      permission: None,
+    };
+    gerrit.check_access("platform/build", &input).unwrap();
+    let paths: Vec<String> = mock.requests().into_iter().map(|r| r.path).collect();
+    assert_eq!(
+      paths,
+      vec![
+        "a/projects/platform%2Fbuild/HEAD",
+        "a/projects/platform%2Fbuild/config",
+        "a/projects/platform%2Fbuild/check.access?account=1000096",
+      ]
+    );
+  }
+}
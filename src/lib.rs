@@ -6,27 +6,75 @@ extern crate strum_macros;
 
 use crate::handler::RestHandler;
 use crate::http::HttpRequestHandler;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
 use url::Url;
 
 pub mod accounts;
+#[cfg(feature = "async")]
+pub mod asynchronous;
 pub mod changes;
+pub mod config;
 pub mod details;
 pub mod error;
+pub mod groups;
 pub mod projects;
 
 mod handler;
 mod http;
 mod r#impl;
 
+pub use crate::handler::RetryConfig;
 pub use crate::http::AuthMethod as HttpAuthMethod;
+pub use crate::http::Error as HttpError;
+pub use crate::http::Header as HttpHeader;
+pub use crate::http::Transport;
 
 pub type Result<T> = std::result::Result<T, crate::error::Error>;
 
 /// Gerrit REST API over HTTP.
 ///
 /// The API is suitable for automated tools to build upon, as well as supporting some ad-hoc scripting use cases.
-pub struct GerritRestApi {
-  rest: RestHandler,
+///
+/// The underlying HTTP handle can be moved to another thread (`Send`) but not shared between threads (`!Sync`).
+/// Use [ThreadSafeGerrit](struct.ThreadSafeGerrit.html) to share a client across threads.
+///
+/// Requests are performed by libcurl by default. Another [Transport](trait.Transport.html) can be given
+/// with `with_transport`, e.g. to serve canned responses in tests.
+pub struct GerritRestApi<T: Transport = HttpRequestHandler> {
+  rest: RestHandler<T>,
+  default_tag: Option<String>,
+}
+
+impl<T: Transport> GerritRestApi<T> {
+  /// Create a new GerritRestApi that performs its requests through the given transport.
+  pub fn with_transport(transport: T) -> Self {
+    Self {
+      rest: RestHandler::new(transport),
+      default_tag: None,
+    }
+  }
+
+  /// Set a tag to apply to reviews posted with `set_review` that do not specify one.
+  ///
+  /// Useful for CI and other automated accounts, e.g. with an `autogenerated:` prefix so their votes and
+  /// comments can be filtered out in the web UI. An explicit `ReviewInput.tag` always takes precedence.
+  ///
+  /// Inline comments are left untagged, as the tag of a review applies to its inline comments too and
+  /// `CommentInput.tag` is only allowed on drafts, see
+  /// [ReviewInput](https://gerrit-review.googlesource.com/Documentation/rest-api-changes.html#review-input).
+  pub fn default_tag(&mut self, tag: Option<String>) {
+    self.default_tag = tag;
+  }
+
+  /// Retry requests that failed with a transient error, according to the given policy.
+  ///
+  /// By default requests are not retried. See [RetryConfig](struct.RetryConfig.html).
+  pub fn retry(mut self, config: RetryConfig) -> Self {
+    self.rest = self.rest.retry(Some(config));
+    self
+  }
 }
 
 impl GerritRestApi {
@@ -36,18 +84,352 @@ impl GerritRestApi {
   pub fn new(base_url: Url, username: &str, password: &str) -> Result<Self> {
     let http = HttpRequestHandler::new(base_url, username, password)?;
     let rest = RestHandler::new(http);
-    Ok(Self { rest })
+    Ok(Self {
+      rest,
+      default_tag: None,
+    })
   }
 
   /// Specify the HTTP authentication method.
   pub fn http_auth(mut self, auth: &HttpAuthMethod) -> Result<Self> {
-    self.rest = RestHandler::new(self.rest.http().http_auth(auth)?);
+    self.rest = self.rest.map_http(|http| http.http_auth(auth))?;
+    Ok(self)
+  }
+
+  /// Authenticate with a bearer token, e.g. for Gerrit behind an OAuth proxy.
+  ///
+  /// The token is sent as `Authorization: Bearer <token>` on every request,
+  /// and the username and password given at creation are not sent at all.
+  pub fn bearer_auth(mut self, token: &str) -> Result<Self> {
+    self.rest = self.rest.map_http(|http| http.bearer_auth(token))?;
     Ok(self)
   }
 
   /// Enable/Disable SSL verification of both host and peer.
   pub fn ssl_verify(mut self, enable: bool) -> Result<Self> {
-    self.rest = RestHandler::new(self.rest.http().ssl_verify(enable)?);
+    self.rest = self.rest.map_http(|http| http.ssl_verify(enable))?;
+    Ok(self)
+  }
+
+  /// Enable/Disable SSL verification that the server certificate is for the server host name.
+  ///
+  /// Useful e.g. behind a proxy whose certificate is valid but issued for another host name.
+  /// Enabled by default.
+  pub fn ssl_verify_host(mut self, enable: bool) -> Result<Self> {
+    self.rest = self.rest.map_http(|http| http.ssl_verify_host(enable))?;
+    Ok(self)
+  }
+
+  /// Enable/Disable SSL verification of the authenticity of the server certificate.
+  ///
+  /// Enabled by default.
+  pub fn ssl_verify_peer(mut self, enable: bool) -> Result<Self> {
+    self.rest = self.rest.map_http(|http| http.ssl_verify_peer(enable))?;
+    Ok(self)
+  }
+
+  /// Trust the CA certificates in the given PEM file to verify the server certificate,
+  /// e.g. for a server with a certificate issued by an internal CA.
+  ///
+  /// Fails with `Error::Io` if the file cannot be read.
+  pub fn ca_cert(mut self, path: impl AsRef<Path>) -> Result<Self> {
+    let path = path.as_ref();
+    check_readable(path, "CA certificate")?;
+    self.rest = self.rest.map_http(|http| http.ca_cert(path))?;
+    Ok(self)
+  }
+
+  /// Trust the given PEM encoded CA certificates to verify the server certificate.
+  pub fn ca_cert_blob(mut self, pem: &[u8]) -> Result<Self> {
+    self.rest = self.rest.map_http(|http| http.ca_cert_blob(pem))?;
+    Ok(self)
+  }
+
+  /// Authenticate with a client certificate (mTLS), given as PEM files for the certificate and its private key,
+  /// with the password of the private key if it is encrypted.
+  ///
+  /// This is independent of the HTTP authentication, which still applies.
+  /// Fails with `Error::Io` if either file cannot be read.
+  pub fn client_cert(
+    mut self, cert: impl AsRef<Path>, key: impl AsRef<Path>, key_password: Option<&str>,
+  ) -> Result<Self> {
+    let (cert, key) = (cert.as_ref(), key.as_ref());
+    check_readable(cert, "client certificate")?;
+    check_readable(key, "client certificate key")?;
+    self.rest = self.rest.map_http(|http| http.client_cert(cert, key, key_password))?;
+    Ok(self)
+  }
+
+  /// Set the maximum time a whole request is allowed to take, including connecting.
+  ///
+  /// Requests that exceed it fail with `Error::Timeout`. By default there is no timeout.
+  pub fn timeout(mut self, timeout: Duration) -> Result<Self> {
+    self.rest = self.rest.map_http(|http| http.timeout(timeout))?;
+    Ok(self)
+  }
+
+  /// Set the maximum time allowed for connecting to the server.
+  ///
+  /// Connections that exceed it fail with `Error::Timeout`. By default the libcurl default of 300 seconds applies.
+  pub fn connect_timeout(mut self, timeout: Duration) -> Result<Self> {
+    self.rest = self.rest.map_http(|http| http.connect_timeout(timeout))?;
+    Ok(self)
+  }
+
+  /// Enable/Disable TCP keep-alive probes.
+  ///
+  /// Useful for long-lived clients whose idle connections may otherwise be dropped by a NAT or firewall.
+  /// Disabled by default.
+  pub fn tcp_keepalive(mut self, enable: bool) -> Result<Self> {
+    self.rest = self.rest.map_http(|http| http.tcp_keepalive(enable))?;
     Ok(self)
   }
+
+  /// Set the time the connection must be idle before keep-alive probes are sent.
+  ///
+  /// Only takes effect with `tcp_keepalive` enabled. By default the libcurl default of 60 seconds applies.
+  pub fn tcp_keepidle(mut self, idle: Duration) -> Result<Self> {
+    self.rest = self.rest.map_http(|http| http.tcp_keepidle(idle))?;
+    Ok(self)
+  }
+}
+
+/// Check that the file at `path` can be read, so that a bad path is reported before any request is made.
+fn check_readable(path: &Path, what: &str) -> Result<()> {
+  std::fs::File::open(path)
+    .map_err(|e| std::io::Error::new(e.kind(), format!("cannot read {} '{}': {}", what, path.display(), e)))?;
+  Ok(())
+}
+
+/// Thread-safe wrapper around GerritRestApi.
+///
+/// The client is held behind a `Mutex`, so requests issued from different threads are serialized.
+/// The wrapper is `Sync` and can be shared e.g. in an `Arc`; all endpoint methods are available
+/// with `&self` through `lock` and `with`.
+pub struct ThreadSafeGerrit<T: Transport = HttpRequestHandler> {
+  inner: Mutex<GerritRestApi<T>>,
+}
+
+impl<T: Transport> ThreadSafeGerrit<T> {
+  /// Wrap a GerritRestApi to be shared across threads.
+  pub fn new(gerrit: GerritRestApi<T>) -> Self {
+    Self {
+      inner: Mutex::new(gerrit),
+    }
+  }
+
+  /// Lock the client for exclusive use by the calling thread, giving access to all endpoint methods.
+  pub fn lock(&self) -> MutexGuard<'_, GerritRestApi<T>> {
+    self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+
+  /// Run `f` with exclusive access to the client.
+  pub fn with<F, R>(&self, f: F) -> R
+  where
+    F: FnOnce(&mut GerritRestApi<T>) -> R,
+  {
+    f(&mut self.lock())
+  }
+
+  /// Unwrap the inner GerritRestApi.
+  pub fn into_inner(self) -> GerritRestApi<T> {
+    self.inner.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+}
+
+impl<T: Transport> From<GerritRestApi<T>> for ThreadSafeGerrit<T> {
+  fn from(gerrit: GerritRestApi<T>) -> Self {
+    Self::new(gerrit)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::changes::ChangeEndpoints;
+  use crate::http::mock::{MockTransport, CHANGE};
+  use std::sync::Arc;
+
+  fn assert_send<T: Send>() {}
+  fn assert_sync<T: Sync>() {}
+
+  #[test]
+  fn thread_safety() {
+    assert_send::<GerritRestApi>();
+    assert_send::<ThreadSafeGerrit>();
+    assert_sync::<ThreadSafeGerrit>();
+    assert_sync::<ThreadSafeGerrit<MockTransport>>();
+  }
+
+  #[test]
+  fn request_timeout() {
+    use crate::config::ConfigEndpoints;
+    // Connections are queued by the listener but never answered.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+    let mut gerrit = GerritRestApi::new(url, "jdoe", "secret")
+      .unwrap()
+      .timeout(Duration::from_millis(100))
+      .unwrap();
+    let error = gerrit.get_version().unwrap_err();
+    assert!(matches!(error, crate::error::Error::Timeout(_)), "{:?}", error);
+    assert_eq!(error.to_string(), "Request timed out");
+  }
+
+  #[test]
+  #[ignore = "requires a network where 10.255.255.1 is unroutable"]
+  fn connect_timeout() {
+    use crate::config::ConfigEndpoints;
+    let url = Url::parse("http://10.255.255.1/").unwrap();
+    let mut gerrit = GerritRestApi::new(url, "jdoe", "secret")
+      .unwrap()
+      .connect_timeout(Duration::from_millis(100))
+      .unwrap();
+    let error = gerrit.get_version().unwrap_err();
+    assert!(matches!(error, crate::error::Error::Timeout(_)), "{:?}", error);
+  }
+
+  /// Request the server version with a client configured by `configure`,
+  /// returning the result along with whether the server served the request.
+  fn get_version_tls(
+    identity: &http::tls::Identity, client_ca: Option<&http::tls::TestCa>,
+    configure: impl FnOnce(GerritRestApi) -> Result<GerritRestApi>,
+  ) -> (Result<String>, bool) {
+    use crate::config::ConfigEndpoints;
+    let (url, server) = http::tls::serve(identity, client_ca, ")]}'\n\"3.4.0\"");
+    let gerrit = GerritRestApi::new(url, "jdoe", "secret")
+      .and_then(|gerrit| gerrit.timeout(Duration::from_secs(5)))
+      .and_then(configure);
+    let version = gerrit.and_then(|mut gerrit| gerrit.get_version());
+    (version, server.join().unwrap())
+  }
+
+  fn write_pem(dir: &tempfile::TempDir, name: &str, pem: &[u8]) -> std::path::PathBuf {
+    let path = dir.path().join(name);
+    std::fs::write(&path, pem).unwrap();
+    path
+  }
+
+  #[test]
+  fn ssl_verify_host_and_peer_are_independent() {
+    let ca = http::tls::TestCa::new();
+    // Issued by an untrusted CA for another host name, so both checks fail by default.
+    let identity = ca.issue("gerrit.example");
+    let (version, served) = get_version_tls(&identity, None, Ok);
+    assert!(version.is_err() && !served);
+    let (version, served) = get_version_tls(&identity, None, |g| g.ssl_verify_peer(false));
+    assert!(version.is_err() && !served, "host name must still be verified");
+    let (version, served) = get_version_tls(&identity, None, |g| g.ssl_verify_host(false));
+    assert!(version.is_err() && !served, "certificate must still be verified");
+    let (version, _) = get_version_tls(&identity, None, |g| g.ssl_verify_host(false)?.ssl_verify_peer(false));
+    assert_eq!(version.unwrap(), "3.4.0");
+    let (version, _) = get_version_tls(&identity, None, |g| g.ssl_verify(false));
+    assert_eq!(version.unwrap(), "3.4.0");
+    // With the CA trusted only the host name check fails.
+    let ca_pem = ca.cert.to_pem().unwrap();
+    let (version, served) = get_version_tls(&identity, None, |g| g.ca_cert_blob(&ca_pem));
+    assert!(version.is_err() && !served);
+    let (version, _) = get_version_tls(&identity, None, |g| g.ca_cert_blob(&ca_pem)?.ssl_verify_host(false));
+    assert_eq!(version.unwrap(), "3.4.0");
+  }
+
+  #[test]
+  fn ca_cert() {
+    let ca = http::tls::TestCa::new();
+    let identity = ca.issue("127.0.0.1");
+    let dir = tempfile::tempdir().unwrap();
+    let ca_pem = ca.cert.to_pem().unwrap();
+    let ca_path = write_pem(&dir, "ca.pem", &ca_pem);
+    let (version, served) = get_version_tls(&identity, None, Ok);
+    assert!(version.is_err() && !served);
+    let (version, _) = get_version_tls(&identity, None, |g| g.ca_cert(&ca_path));
+    assert_eq!(version.unwrap(), "3.4.0");
+    let (version, _) = get_version_tls(&identity, None, |g| g.ca_cert_blob(&ca_pem));
+    assert_eq!(version.unwrap(), "3.4.0");
+  }
+
+  #[test]
+  fn missing_ca_cert_is_an_io_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("missing.pem");
+    let url = Url::parse("https://127.0.0.1/").unwrap();
+    let error = GerritRestApi::new(url, "jdoe", "secret")
+      .unwrap()
+      .ca_cert(&path)
+      .err()
+      .unwrap();
+    match error {
+      crate::error::Error::Io(e) => {
+        assert_eq!(e.kind(), std::io::ErrorKind::NotFound);
+        assert!(e
+          .to_string()
+          .starts_with(&format!("cannot read CA certificate '{}'", path.display())));
+      }
+      e => panic!("unexpected error: {:?}", e),
+    }
+  }
+
+  #[test]
+  fn client_cert() {
+    use openssl::symm::Cipher;
+    let ca = http::tls::TestCa::new();
+    let identity = ca.issue("127.0.0.1");
+    let client = ca.issue("jdoe");
+    let dir = tempfile::tempdir().unwrap();
+    let ca_pem = ca.cert.to_pem().unwrap();
+    let cert = write_pem(&dir, "client.pem", &client.cert.to_pem().unwrap());
+    let key = write_pem(&dir, "client.key", &client.key.private_key_to_pem_pkcs8().unwrap());
+    let encrypted_key = client
+      .key
+      .private_key_to_pem_pkcs8_passphrase(Cipher::aes_256_cbc(), b"hunter2")
+      .unwrap();
+    let encrypted_key = write_pem(&dir, "client-encrypted.key", &encrypted_key);
+    let (version, served) = get_version_tls(&identity, Some(&ca), |g| g.ca_cert_blob(&ca_pem));
+    assert!(version.is_err() && !served, "a client certificate is required");
+    let (version, _) = get_version_tls(&identity, Some(&ca), |g| {
+      g.ca_cert_blob(&ca_pem)?.client_cert(&cert, &key, None)
+    });
+    assert_eq!(version.unwrap(), "3.4.0");
+    let (version, _) = get_version_tls(&identity, Some(&ca), |g| {
+      g.ca_cert_blob(&ca_pem)?
+        .client_cert(&cert, &encrypted_key, Some("hunter2"))
+    });
+    assert_eq!(version.unwrap(), "3.4.0");
+  }
+
+  #[test]
+  fn unreadable_client_cert_is_an_io_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let existing = write_pem(&dir, "client.pem", b"");
+    let missing = dir.path().join("missing.pem");
+    let client_cert = |cert: &std::path::Path, key: &std::path::Path| {
+      let url = Url::parse("https://127.0.0.1/").unwrap();
+      let gerrit = GerritRestApi::new(url, "jdoe", "secret").unwrap();
+      match gerrit.client_cert(cert, key, None).err().unwrap() {
+        crate::error::Error::Io(e) => e,
+        e => panic!("unexpected error: {:?}", e),
+      }
+    };
+    let error = client_cert(&missing, &existing);
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+    assert!(error.to_string().starts_with("cannot read client certificate '"));
+    let error = client_cert(&existing, &missing);
+    assert!(error.to_string().starts_with("cannot read client certificate key '"));
+  }
+
+  #[test]
+  fn thread_safe_gerrit_is_shared_across_threads() {
+    let mock = MockTransport::new().respond_json(200, CHANGE).respond_json(200, CHANGE);
+    let gerrit = Arc::new(ThreadSafeGerrit::new(mock.gerrit()));
+    let threads: Vec<_> = (0..2)
+      .map(|_| {
+        let gerrit = Arc::clone(&gerrit);
+        std::thread::spawn(move || gerrit.with(|gerrit| gerrit.get_change("3965", None)).unwrap())
+      })
+      .collect();
+    for thread in threads {
+      assert_eq!(thread.join().unwrap().number, 3965);
+    }
+    assert_eq!(mock.requests().len(), 2);
+  }
 }
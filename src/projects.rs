@@ -2,23 +2,114 @@
 //!
 //! See [ProjectEndpoints](trait.ProjectEndpoints.html) trait for the REST API.
 
-use crate::changes::WebLinkInfo;
+use crate::changes::{ActionInfo, GitPersonInfo, SubmitType, WebLinkInfo};
+use crate::details::Timestamp;
+use crate::error::Error;
+use crate::groups::GroupInfo;
+use crate::Result;
 use serde_derive::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 // ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
 // REST API
 // ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
 
 /// This trait describes the project related REST endpoints.
-pub trait ProjectEndpoints {}
+pub trait ProjectEndpoints {
+  /// Lists the projects accessible by the caller.
+  ///
+  /// This is the same as using the ls-projects command over SSH, and accepts the same options as query parameters.
+  ///
+  /// As result a map is returned that maps the project names to `ProjectInfo` entries.
+  /// The entries in the map are sorted by project name.
+  /// The `name` of each entry is filled from its map key, see `fill_project_names`.
+  fn list_projects(&mut self, params: &ProjectQueryParams) -> Result<BTreeMap<String, ProjectInfo>>;
+
+  /// Retrieves a project.
+  ///
+  /// As response a `ProjectInfo` entity is returned that describes the project.
+  fn get_project(&mut self, name: &str) -> Result<ProjectInfo>;
+
+  /// Creates a new project.
+  ///
+  /// In the request body additional data for the project can be provided as `ProjectInput`.
+  ///
+  /// As response the `ProjectInfo` entity is returned that describes the created project.
+  fn create_project(&mut self, name: &str, input: &ProjectInput) -> Result<ProjectInfo>;
+
+  /// Retrieves for a project the name of the branch to which HEAD points.
+  fn get_head(&mut self, project: &str) -> Result<String>;
+
+  /// Sets HEAD for a project.
+  ///
+  /// The new ref to which HEAD should point must be provided in the request body inside a `HeadInput` entity.
+  /// The ref must start with `refs/`, which is checked before sending the request.
+  ///
+  /// As response the new ref to which HEAD points is returned.
+  fn set_head(&mut self, project: &str, input: &HeadInput) -> Result<String>;
+
+  /// Gets some configuration information about a project.
+  ///
+  /// Note that this config info is not simply the contents of project.config; it generally contains
+  /// fields that may have been inherited from parent projects.
+  ///
+  /// A `ConfigInfo` entity is returned that describes the project configuration.
+  /// Some fields are only visible to users that have read access to refs/meta/config.
+  fn get_project_config(&mut self, project: &str) -> Result<ConfigInfo>;
+
+  /// Sets the configuration of a project.
+  ///
+  /// The new values for the project config must be provided in the request body as a `ConfigInput` entity.
+  ///
+  /// As response the new configuration is returned as a `ConfigInfo` entity.
+  fn set_project_config(&mut self, project: &str, input: &ConfigInput) -> Result<ConfigInfo>;
+
+  /// Creates a new tag on the project.
+  ///
+  /// In the request body additional data for the tag can be provided as `TagInput`.
+  /// If a message is provided in the input, the tag is created as annotated tag with the current user as tagger.
+  /// Otherwise a lightweight tag is created. The input is validated before sending the request.
+  ///
+  /// As response a `TagInfo` entity is returned that describes the created tag.
+  fn create_tag(&mut self, project: &str, tag: &str, input: &TagInput) -> Result<TagInfo>;
+
+  /// Lists the access rights for a single project.
+  ///
+  /// As result a `ProjectAccessInfo` entity is returned.
+  fn get_access(&mut self, project: &str) -> Result<ProjectAccessInfo>;
+
+  /// Sets access rights for the project using the diff schema provided by `ProjectAccessInput`.
+  ///
+  /// Deductions are used to remove access sections, permissions or permission rules. The backend will
+  /// remove the entity with the finest granularity in the request, meaning that if an access section
+  /// without permissions is posted, the access section will be removed; if an access section with
+  /// a permission but no permission rules is posted, the permission will be removed; if an access
+  /// section with a permission and a permission rule is posted, the permission rule will be removed.
+  ///
+  /// Additionally, access sections and permissions will be cleaned up after applying the deductions
+  /// by removing items that have no child elements.
+  ///
+  /// After removals have been applied, additions will be applied.
+  ///
+  /// As result a `ProjectAccessInfo` entity is returned.
+  fn set_access(&mut self, project: &str, input: &ProjectAccessInput) -> Result<ProjectAccessInfo>;
+
+  /// Runs access checks for other users.
+  ///
+  /// The account, and optionally the ref and permission to check, are given by `CheckAccessInput`.
+  /// This requires the View Access global capability.
+  ///
+  /// As result an `AccessCheckInfo` entity is returned, which tells whether the access is allowed
+  /// and explains the reason if it is not.
+  fn check_access(&mut self, project: &str, input: &CheckAccessInput) -> Result<AccessCheckInfo>;
+}
 
 // ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
 // JSON Entities
 // ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
 
-#[derive(Debug, Display, Serialize, Deserialize)]
+#[derive(Debug, Display, PartialEq, Eq, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 pub enum ProjectStatus {
@@ -51,5 +142,709 @@ pub struct ProjectInfo {
   pub web_links: Option<Vec<WebLinkInfo>>,
 }
 
+/// Fill the `name` of each project from its key in a map returned by Gerrit.
+///
+/// Gerrit omits the name of a ProjectInfo when the name is used as map key, so this makes it always available.
+pub fn fill_project_names(projects: &mut BTreeMap<String, ProjectInfo>) {
+  for (name, project) in projects.iter_mut() {
+    project.name.get_or_insert_with(|| name.clone());
+  }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LabelTypeInfo {}
+
+/// The HeadInput entity contains information for setting HEAD for a project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeadInput {
+  /// The ref to which HEAD should be set, including the refs/ prefix.
+  #[serde(rename = "ref")]
+  pub refspec: String,
+}
+
+impl HeadInput {
+  /// Create a HeadInput pointing to the given ref, which must start with `refs/`.
+  pub fn new(refspec: &str) -> Result<Self> {
+    let input = Self {
+      refspec: refspec.to_string(),
+    };
+    input.validate()?;
+    Ok(input)
+  }
+
+  /// Check that the ref starts with `refs/`.
+  pub fn validate(&self) -> Result<()> {
+    if !self.refspec.starts_with("refs/") {
+      return Err(Error::InvalidInput(format!(
+        "ref '{}' does not start with 'refs/'",
+        self.refspec
+      )));
+    }
+    Ok(())
+  }
+}
+
+/// Boolean value that can also be inherited from the parent project.
+#[derive(Debug, Display, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum InheritableBoolean {
+  True,
+  False,
+  Inherit,
+}
+
+/// The ProjectInput entity contains information for the creation of a new project.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectInput {
+  /// The name of the project (not encoded).
+  /// If set, must match the project name in the URL.
+  /// If name ends with .git the suffix will be automatically removed.
+  pub name: Option<String>,
+  /// The name of the parent project.
+  /// If not set, the All-Projects project will be the parent project.
+  pub parent: Option<String>,
+  /// The description of the project.
+  pub description: Option<String>,
+  /// Whether a permission-only project should be created.
+  pub permissions_only: Option<bool>,
+  /// Whether an empty initial commit should be created.
+  pub create_empty_commit: Option<bool>,
+  /// The submit type that should be set for the project.
+  /// If not set, INHERIT is used.
+  pub submit_type: Option<SubmitType>,
+  /// A list of branches that should be initially created.
+  /// For the branch names the refs/heads/ prefix can be omitted.
+  pub branches: Option<Vec<String>>,
+  /// A list of groups that should be assigned as project owner.
+  /// Each group in the list must be specified as group-id.
+  /// If not set, the groups that are configured as default owners are set as project owners.
+  pub owners: Option<Vec<String>>,
+  /// Whether contributor agreements should be used for the project.
+  pub use_contributor_agreements: Option<InheritableBoolean>,
+  /// Whether the usage of 'Signed-Off-By' footers is required for the project.
+  pub use_signed_off_by: Option<InheritableBoolean>,
+  /// Whether a new change is created for every commit not in target branch for the project.
+  pub create_new_change_for_all_not_in_target: Option<InheritableBoolean>,
+  /// Whether content merge should be enabled for the project.
+  pub use_content_merge: Option<InheritableBoolean>,
+  /// Whether the usage of Change-Ids is required for the project.
+  pub require_change_id: Option<InheritableBoolean>,
+  /// Whether empty commits should be rejected when a change is merged.
+  pub reject_empty_commit: Option<InheritableBoolean>,
+  /// Max allowed Git object size for this project.
+  /// Common unit suffixes of 'k', 'm', or 'g' are supported.
+  pub max_object_size_limit: Option<String>,
+}
+
+/// The CommentLinkInfo entity describes a commentlink.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentLinkInfo {
+  /// A JavaScript regular expression to match positions to be replaced with a hyperlink.
+  #[serde(rename = "match")]
+  pub pattern: String,
+  /// The URL to direct the user to whenever the regular expression is matched.
+  pub link: Option<String>,
+  /// Whether the commentlink is enabled, as documented in commentlink.name.enabled.
+  /// If not set the commentlink is enabled.
+  pub enabled: Option<bool>,
+}
+
+/// The ConfigInfo entity contains information about the effective project configuration.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigInfo {
+  /// The description of the project.
+  pub description: Option<String>,
+  /// InheritedBooleanInfo that tells whether authors must complete a contributor agreement on the site
+  /// before pushing any commits or changes to this project.
+  pub use_contributor_agreements: Option<InheritedBooleanInfo>,
+  /// InheritedBooleanInfo that tells whether Gerrit will try to perform a 3-way merge of text file content
+  /// when a file has been modified by both the destination branch and the change being submitted.
+  /// This option only takes effect if submit type is not FAST_FORWARD_ONLY.
+  pub use_content_merge: Option<InheritedBooleanInfo>,
+  /// InheritedBooleanInfo that tells whether each change must contain a Signed-off-by line from either
+  /// the author or the uploader in the commit message.
+  pub use_signed_off_by: Option<InheritedBooleanInfo>,
+  /// InheritedBooleanInfo that tells whether a new change is created for every commit not in target branch.
+  pub create_new_change_for_all_not_in_target: Option<InheritedBooleanInfo>,
+  /// InheritedBooleanInfo that tells whether a valid Change-Id footer in any commit uploaded for review is required.
+  pub require_change_id: Option<InheritedBooleanInfo>,
+  /// InheritedBooleanInfo that tells whether empty commits should be rejected when a change is merged.
+  pub reject_empty_commit: Option<InheritedBooleanInfo>,
+  /// The MaxObjectSizeLimitInfo entity.
+  pub max_object_size_limit: Option<MaxObjectSizeLimitInfo>,
+  /// The default submit type of the project.
+  pub submit_type: Option<SubmitType>,
+  /// The state of the project.
+  pub state: Option<ProjectStatus>,
+  /// Map with the comment link configurations of the project.
+  /// The name of the comment link configuration is mapped to the comment link configuration.
+  pub commentlinks: Option<BTreeMap<String, CommentLinkInfo>>,
+  /// Plugin configuration as map which maps the plugin name to a map of parameter names to
+  /// ConfigParameterInfo entities.
+  pub plugin_config: Option<BTreeMap<String, BTreeMap<String, ConfigParameterInfo>>>,
+  /// Actions the caller might be able to perform on this project.
+  /// The information is a map of view name to ActionInfo entities.
+  pub actions: Option<BTreeMap<String, ActionInfo>>,
+}
+
+/// The ConfigInput entity describes a new project configuration.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigInput {
+  /// The new description of the project.
+  /// If not set, the description is removed.
+  pub description: Option<String>,
+  /// Whether authors must complete a contributor agreement on the site before pushing any commits
+  /// or changes to this project.
+  pub use_contributor_agreements: Option<InheritableBoolean>,
+  /// Whether Gerrit will try to perform a 3-way merge of text file content when a file has been
+  /// modified by both the destination branch and the change being submitted.
+  pub use_content_merge: Option<InheritableBoolean>,
+  /// Whether each change must contain a Signed-off-by line from either the author or the uploader
+  /// in the commit message.
+  pub use_signed_off_by: Option<InheritableBoolean>,
+  /// Whether a new change is created for every commit not in target branch.
+  pub create_new_change_for_all_not_in_target: Option<InheritableBoolean>,
+  /// Whether a valid Change-Id footer in any commit uploaded for review is required.
+  pub require_change_id: Option<InheritableBoolean>,
+  /// Whether empty commits should be rejected when a change is merged.
+  pub reject_empty_commit: Option<InheritableBoolean>,
+  /// Max allowed Git object size for this project.
+  /// Common unit suffixes of 'k', 'm', or 'g' are supported.
+  pub max_object_size_limit: Option<String>,
+  /// The default submit type of the project.
+  pub submit_type: Option<SubmitType>,
+  /// The state of the project.
+  pub state: Option<ProjectStatus>,
+  /// Map with the comment link configurations of the project.
+  /// The name of the comment link configuration is mapped to the comment link configuration.
+  pub commentlinks: Option<BTreeMap<String, CommentLinkInfo>>,
+  /// Plugin configuration values as map which maps the plugin name to a map of parameter names to values.
+  pub plugin_config_values: Option<BTreeMap<String, BTreeMap<String, String>>>,
+}
+
+/// The ConfigParameterInfo entity describes a project configuration parameter.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigParameterInfo {
+  /// The display name of the configuration parameter.
+  pub display_name: Option<String>,
+  /// The description of the configuration parameter.
+  pub description: Option<String>,
+  /// Warning message for the configuration parameter.
+  pub warning: Option<String>,
+  /// The type of the configuration parameter. Can be STRING, INT, LONG, BOOLEAN, LIST or ARRAY.
+  #[serde(rename = "type")]
+  pub param_type: String,
+  /// The value of the configuration parameter as string.
+  /// If the parameter is inheritable this is the effective value which is deduced from
+  /// configured_value and inherited_value.
+  pub value: Option<String>,
+  /// The list of values. Only set if the type is ARRAY.
+  pub values: Option<Vec<String>>,
+  /// Whether the value is editable.
+  #[serde(default)]
+  pub editable: bool,
+  /// The list of permitted values, only set if the type is LIST.
+  pub permitted_values: Option<Vec<String>>,
+  /// Whether the configuration parameter can be inherited.
+  #[serde(default)]
+  pub inheritable: bool,
+  /// The value of the configuration parameter that is configured on this project,
+  /// only set if inheritable.
+  pub configured_value: Option<String>,
+  /// The inherited value of the configuration parameter, only set if inheritable.
+  pub inherited_value: Option<String>,
+}
+
+/// A boolean value that can also be inherited.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InheritedBooleanInfo {
+  /// The effective boolean value.
+  pub value: bool,
+  /// The configured value, can be TRUE, FALSE or INHERIT.
+  pub configured_value: InheritableBoolean,
+  /// The boolean value inherited from the parent.
+  /// Not set if there is no parent.
+  pub inherited_value: Option<bool>,
+}
+
+/// The MaxObjectSizeLimitInfo entity contains information about the max object size limit of a project.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaxObjectSizeLimitInfo {
+  /// The effective value of the max object size limit as a formatted string.
+  /// Not set if there is no limit for the object size.
+  pub value: Option<String>,
+  /// The max object size limit of this project as a formatted string.
+  /// Not set if there is no limit for the object size configured on project level.
+  pub configured_value: Option<String>,
+  /// A summary of the effective value of the max object size limit.
+  /// Not set if there is no limit for the object size.
+  pub summary: Option<String>,
+}
+
+/// The TagInfo entity contains information about a tag.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagInfo {
+  /// The ref of the tag.
+  #[serde(rename = "ref")]
+  pub refspec: String,
+  /// For lightweight tags, the revision of the commit to which the tag points.
+  /// For annotated tags, the revision of the tag object.
+  pub revision: String,
+  /// Only set for annotated tags.
+  /// The revision of the object to which the tag points.
+  pub object: Option<String>,
+  /// Only set for annotated tags.
+  /// The tag message. For signed tags, includes the signature.
+  pub message: Option<String>,
+  /// Only set for annotated tags, if present in the tag.
+  /// The tagger as a GitPersonInfo entity.
+  pub tagger: Option<GitPersonInfo>,
+  /// The timestamp of when the tag was created.
+  /// For annotated and signed tags, this is the timestamp of the tag object and is the same as the
+  /// date field in the tagger. For lightweight tags, it is the commit timestamp of the commit to
+  /// which the tag points, when the object is a commit. It is not set when the object is any other type.
+  pub created: Option<Timestamp>,
+  /// Whether the calling user can delete this tag.
+  #[serde(default)]
+  pub can_delete: bool,
+  /// Links to the tag in external sites as a list of WebLinkInfo entries.
+  pub web_links: Option<Vec<WebLinkInfo>>,
+}
+
+/// The TagInput entity contains information for creating a tag.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagInput {
+  /// The name of the tag. The leading refs/tags/ is optional.
+  /// If set, must match the tag name in the URL.
+  #[serde(rename = "ref")]
+  pub refspec: Option<String>,
+  /// The revision to which the tag should point.
+  /// If not specified, the project’s HEAD will be used.
+  pub revision: Option<String>,
+  /// The tag message.
+  /// When set, the tag will be created as an annotated tag.
+  pub message: Option<String>,
+}
+
+impl TagInput {
+  /// Create the input for a lightweight tag pointing to the given revision.
+  pub fn lightweight(revision: Option<String>) -> Self {
+    Self {
+      refspec: None,
+      revision,
+      message: None,
+    }
+  }
+
+  /// Create the input for an annotated tag with the given message pointing to the given revision.
+  pub fn annotated(revision: Option<String>, message: &str) -> Self {
+    Self {
+      refspec: None,
+      revision,
+      message: Some(message.to_string()),
+    }
+  }
+
+  /// Whether the tag will be created as an annotated tag.
+  pub fn is_annotated(&self) -> bool {
+    self.message.is_some()
+  }
+
+  /// Check the input for creating the tag named `tag`.
+  ///
+  /// An annotated tag must have a non-blank message, and the ref, if set, must name the same tag.
+  pub fn validate(&self, tag: &str) -> Result<()> {
+    if let Some(message) = &self.message {
+      if message.trim().is_empty() {
+        return Err(Error::InvalidInput("annotated tag message must not be empty".into()));
+      }
+    }
+    if let Some(refspec) = &self.refspec {
+      if refspec.trim_start_matches("refs/tags/") != tag.trim_start_matches("refs/tags/") {
+        return Err(Error::InvalidInput(format!(
+          "tag ref '{}' does not match tag name '{}'",
+          refspec, tag
+        )));
+      }
+    }
+    Ok(())
+  }
+}
+
+/// The AccessCheckInfo entity is the result of an access check.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessCheckInfo {
+  /// The HTTP status code for the access. 200 means success and 403 means denied.
+  pub status: u16,
+  /// A clarifying message if status is not 200.
+  pub message: Option<String>,
+  /// Debug information useful to track down why the access was allowed or denied.
+  pub debug_logs: Option<Vec<String>>,
+}
+
+impl AccessCheckInfo {
+  /// Whether the access is allowed.
+  pub fn is_allowed(&self) -> bool {
+    self.status == 200
+  }
+}
+
+/// The AccessSectionInfo describes the access rights that are assigned on a ref.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccessSectionInfo {
+  /// The permissions assigned on the ref of this access section.
+  /// The key is the permission name, e.g. `push` or `label-Code-Review`.
+  #[serde(default)]
+  pub permissions: BTreeMap<String, PermissionInfo>,
+}
+
+/// The PermissionInfo entity contains information about an assigned permission.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PermissionInfo {
+  /// The name of the label. Not set if it’s not a label permission.
+  pub label: Option<String>,
+  /// Whether this permission is assigned exclusively.
+  pub exclusive: Option<bool>,
+  /// Map of group UUIDs to PermissionRuleInfo entities.
+  #[serde(default)]
+  pub rules: BTreeMap<String, PermissionRuleInfo>,
+}
+
+/// The action of a permission rule.
+#[derive(Debug, Display, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum PermissionRuleAction {
+  Allow,
+  Deny,
+  Block,
+  Interactive,
+  Batch,
+}
+
+/// The PermissionRuleInfo entity contains information about a permission rule that is assigned to a group.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionRuleInfo {
+  /// The action of this rule.
+  pub action: PermissionRuleAction,
+  /// Whether the force flag is set.
+  pub force: Option<bool>,
+  /// The min value of the permission range.
+  pub min: Option<i32>,
+  /// The max value of the permission range.
+  pub max: Option<i32>,
+}
+
+impl PermissionRuleInfo {
+  /// Create a rule with the given action and no force flag or range.
+  pub fn new(action: PermissionRuleAction) -> Self {
+    Self {
+      action,
+      force: None,
+      min: None,
+      max: None,
+    }
+  }
+
+  /// Create an allow rule for a label permission with the given range of values.
+  pub fn label_range(min: i32, max: i32) -> Self {
+    Self {
+      action: PermissionRuleAction::Allow,
+      force: None,
+      min: Some(min),
+      max: Some(max),
+    }
+  }
+}
+
+/// The ProjectAccessInfo entity contains information about the access rights for a project.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectAccessInfo {
+  /// The revision of the refs/meta/config branch from which the access rights were loaded.
+  pub revision: String,
+  /// The parent project from which permissions are inherited as a ProjectInfo entity.
+  pub inherits_from: Option<ProjectInfo>,
+  /// The local access rights of the project as a map that maps the refs to AccessSectionInfo entities.
+  #[serde(default)]
+  pub local: BTreeMap<String, AccessSectionInfo>,
+  /// Whether the calling user owns this project.
+  #[serde(default)]
+  pub is_owner: bool,
+  /// The list of refs owned by the calling user.
+  pub owner_of: Option<Vec<String>>,
+  /// Whether the calling user can upload to any ref.
+  #[serde(default)]
+  pub can_upload: bool,
+  /// Whether the calling user can add any ref.
+  #[serde(default)]
+  pub can_add: bool,
+  /// Whether the calling user can add any tag ref.
+  #[serde(default)]
+  pub can_add_tags: bool,
+  /// Whether the calling user can see the refs/meta/config branch of the project.
+  #[serde(default)]
+  pub config_visible: bool,
+  /// A map of group UUID to GroupInfo objects, with names and URLs for the group UUIDs used in the local map.
+  pub groups: Option<BTreeMap<String, GroupInfo>>,
+  /// Links to the history of the configuration file governing this project’s access rights.
+  pub config_web_links: Option<Vec<WebLinkInfo>>,
+}
+
+/// The ProjectAccessInput describes changes that should be applied to a project access config.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectAccessInput {
+  /// A list of deductions to be applied to the project access as ProjectAccessInfo entities.
+  pub remove: Option<BTreeMap<String, AccessSectionInfo>>,
+  /// A list of additions to be applied to the project access as ProjectAccessInfo entities.
+  pub add: Option<BTreeMap<String, AccessSectionInfo>>,
+  /// A commit message for this change.
+  pub message: Option<String>,
+  /// A new parent for the project to inherit from. Changing the parent project requires administrative privileges.
+  pub parent: Option<String>,
+}
+
+impl ProjectAccessInput {
+  /// Add the permission rule of the group with the given UUID to the permission on the ref.
+  pub fn add_rule(mut self, refspec: &str, permission: &str, group_id: &str, rule: PermissionRuleInfo) -> Self {
+    Self::insert_rule(
+      self.add.get_or_insert_with(Default::default),
+      refspec,
+      permission,
+      group_id,
+      rule,
+    );
+    self
+  }
+
+  /// Remove the permission rule of the group with the given UUID from the permission on the ref.
+  ///
+  /// Gerrit matches deductions by ref, permission and group only and ignores the rule body, which is
+  /// still required by the schema, so a plain `ALLOW` rule is sent as in the
+  /// [access example](https://gerrit-review.googlesource.com/Documentation/rest-api-projects.html#set-access).
+  pub fn remove_rule(mut self, refspec: &str, permission: &str, group_id: &str) -> Self {
+    let rule = PermissionRuleInfo::new(PermissionRuleAction::Allow);
+    Self::insert_rule(
+      self.remove.get_or_insert_with(Default::default),
+      refspec,
+      permission,
+      group_id,
+      rule,
+    );
+    self
+  }
+
+  fn insert_rule(
+    sections: &mut BTreeMap<String, AccessSectionInfo>, refspec: &str, permission: &str, group_id: &str,
+    rule: PermissionRuleInfo,
+  ) {
+    sections
+      .entry(refspec.into())
+      .or_default()
+      .permissions
+      .entry(permission.into())
+      .or_default()
+      .rules
+      .insert(group_id.into(), rule);
+  }
+}
+
+// ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+// OPTIONS
+// ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Query parameters of the check access endpoint.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckAccessInput {
+  /// The account for which to check access. Mandatory.
+  pub account: String,
+  /// The refname for which to check access.
+  #[serde(rename = "ref")]
+  pub refspec: Option<String>,
+  /// The ref permission for which to check access. If not specified, read access is checked.
+  /// Requires the ref to be set as well.
+  #[serde(rename = "perm")]
+  pub permission: Option<String>,
+}
+
+/// Query parameters available for the list projects endpoint.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProjectQueryParams {
+  /// Limit the results to those projects that start with the specified prefix.
+  #[serde(rename = "p")]
+  pub prefix: Option<String>,
+  /// Limit the results to those projects that match the specified regex.
+  #[serde(rename = "r")]
+  pub regex: Option<String>,
+  /// Limit the results to those projects that match the specified substring.
+  #[serde(rename = "m")]
+  pub substring: Option<String>,
+  /// Limit the number of projects to be included in the results.
+  #[serde(rename = "n")]
+  pub limit: Option<u32>,
+  /// Skip the given number of projects from the beginning of the list.
+  #[serde(rename = "S")]
+  pub start: Option<u32>,
+  /// Include project description in the results.
+  #[serde(rename = "d")]
+  #[serde(serialize_with = "crate::details::serde_flag::serialize")]
+  pub description: Option<()>,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn config_info() {
+    let config: ConfigInfo = serde_json::from_str(
+      r##"{
+        "description": "demo project",
+        "use_contributor_agreements": { "value": true, "configured_value": "TRUE", "inherited_value": false },
+        "use_content_merge": { "value": true, "configured_value": "INHERIT", "inherited_value": true },
+        "use_signed_off_by": { "value": false, "configured_value": "INHERIT", "inherited_value": false },
+        "create_new_change_for_all_not_in_target": { "value": false, "configured_value": "INHERIT" },
+        "require_change_id": { "value": false, "configured_value": "FALSE", "inherited_value": true },
+        "max_object_size_limit": { "value": "15m", "configured_value": "15m", "summary": "15m" },
+        "submit_type": "MERGE_IF_NECESSARY",
+        "state": "ACTIVE",
+        "commentlinks": {
+          "changeid": { "match": "(I[0-9a-f]{8,40})", "link": "#/q/$1" }
+        },
+        "plugin_config": {
+          "helloworld": {
+            "language": {
+              "display_name": "Preferred Language",
+              "type": "STRING",
+              "value": "en",
+              "editable": true,
+              "inheritable": true,
+              "configured_value": "en",
+              "inherited_value": "de"
+            }
+          }
+        },
+        "actions": {
+          "cookbook~hello-project": {
+            "method": "POST",
+            "label": "Say hello",
+            "title": "Say hello in different languages",
+            "enabled": true
+          }
+        }
+      }"##,
+    )
+    .unwrap();
+    assert_eq!(config.description.as_deref(), Some("demo project"));
+    let agreements = config.use_contributor_agreements.unwrap();
+    assert!(agreements.value);
+    assert_eq!(agreements.configured_value, InheritableBoolean::True);
+    assert_eq!(agreements.inherited_value, Some(false));
+    let content_merge = config.use_content_merge.unwrap();
+    assert_eq!(content_merge.configured_value, InheritableBoolean::Inherit);
+    assert!(content_merge.value);
+    assert_eq!(
+      config.create_new_change_for_all_not_in_target.unwrap().inherited_value,
+      None
+    );
+    assert_eq!(config.max_object_size_limit.unwrap().value.as_deref(), Some("15m"));
+    assert!(matches!(config.submit_type, Some(SubmitType::MergeIfNecessary)));
+    assert_eq!(config.state, Some(ProjectStatus::Active));
+    assert_eq!(config.commentlinks.unwrap()["changeid"].link.as_deref(), Some("#/q/$1"));
+    let language = &config.plugin_config.unwrap()["helloworld"]["language"];
+    assert_eq!(language.param_type, "STRING");
+    assert_eq!(language.inherited_value.as_deref(), Some("de"));
+    assert!(config.actions.unwrap()["cookbook~hello-project"].enabled);
+  }
+
+  #[test]
+  fn head_input_requires_full_ref() {
+    let error = HeadInput::new("master").unwrap_err();
+    assert!(matches!(error, Error::InvalidInput(_)));
+    assert_eq!(
+      error.to_string(),
+      "Invalid input: ref 'master' does not start with 'refs/'"
+    );
+    let input = HeadInput::new("refs/heads/master").unwrap();
+    assert_eq!(serde_json::to_string(&input).unwrap(), r#"{"ref":"refs/heads/master"}"#);
+  }
+
+  #[test]
+  fn lightweight_tag_input() {
+    let input = TagInput::lightweight(Some("67ebf73496383c6777035e374d2d664009e2aa5c".into()));
+    assert!(!input.is_annotated());
+    input.validate("v1.0").unwrap();
+    assert_eq!(
+      serde_json::to_string(&input).unwrap(),
+      r#"{"revision":"67ebf73496383c6777035e374d2d664009e2aa5c"}"#
+    );
+  }
+
+  #[test]
+  fn annotated_tag_input() {
+    let input = TagInput::annotated(None, "Release 1.0");
+    assert!(input.is_annotated());
+    input.validate("v1.0").unwrap();
+    assert_eq!(serde_json::to_string(&input).unwrap(), r#"{"message":"Release 1.0"}"#);
+  }
+
+  #[test]
+  fn annotated_tag_with_blank_message_is_rejected() {
+    let error = TagInput::annotated(None, "  \n").validate("v1.0").unwrap_err();
+    assert!(matches!(error, Error::InvalidInput(_)));
+  }
+
+  #[test]
+  fn tag_ref_must_match_tag_name() {
+    let mut input = TagInput::lightweight(None);
+    input.refspec = Some("refs/tags/v1.0".into());
+    input.validate("v1.0").unwrap();
+    input.validate("refs/tags/v1.0").unwrap();
+    let error = input.validate("v2.0").unwrap_err();
+    assert!(matches!(error, Error::InvalidInput(_)));
+  }
+
+  #[test]
+  fn project_access_input() {
+    let input = ProjectAccessInput {
+      message: Some("Let registered users vote".into()),
+      ..Default::default()
+    }
+    .add_rule(
+      "refs/heads/*",
+      "label-Code-Review",
+      "global:Registered-Users",
+      PermissionRuleInfo::label_range(-1, 1),
+    )
+    .remove_rule("refs/heads/*", "label-Code-Review", "global:Anonymous-Users");
+    let json = serde_json::to_value(&input).unwrap();
+    assert_eq!(
+      json,
+      serde_json::json!({
+        "add": { "refs/heads/*": { "permissions": { "label-Code-Review": { "rules": {
+          "global:Registered-Users": { "action": "ALLOW", "min": -1, "max": 1 }
+        } } } } },
+        "remove": { "refs/heads/*": { "permissions": { "label-Code-Review": { "rules": {
+          "global:Anonymous-Users": { "action": "ALLOW" }
+        } } } } },
+        "message": "Let registered users vote"
+      })
+    );
+  }
+}